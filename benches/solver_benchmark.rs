@@ -1,102 +1,250 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use slide_puzzle::solver::{divide_and_conquer::DacPuzzleSolver, optimal::find_swap_order};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkGroup, Criterion};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use slide_puzzle::solver::{
+    beam_search,
+    divide_and_conquer::DacPuzzleSolver,
+    heuristic::{CornerTiles, Heuristic, LastMoves, LinearConflict, ManhattanDistance, Max},
+    ida_star, peephole,
+    registry::{Puzzle, SolverConfig, SolverRegistry},
+};
 
 lazy_static::lazy_static! {
-    static ref PUZZLE_3X3_16: (Vec<u8>, usize, usize) = {
-        (vec![8, 5, 2, 1, 0, 7, 6, 4, 3], 3, 3)
+    static ref PUZZLE_3X3_16: Puzzle = {
+        Puzzle::new(vec![8, 5, 2, 1, 0, 7, 6, 4, 3], 3, 3).unwrap()
     };
-    static ref PUZZLE_4X4_10: (Vec<u8>, usize, usize) = {
-        (vec![0, 1, 2, 3, 4, 5, 6, 7, 12, 8, 10, 11, 13, 14, 9, 15], 4, 4)
+    static ref PUZZLE_4X4_10: Puzzle = {
+        Puzzle::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 12, 8, 10, 11, 13, 14, 9, 15], 4, 4).unwrap()
     };
-    static ref PUZZLE_4X4_17: (Vec<u8>, usize, usize) = {
-        (vec![0, 5, 1, 3, 8, 4, 2, 11, 12, 10, 7, 6, 9, 13, 15, 14], 4, 4)
+    static ref PUZZLE_4X4_17: Puzzle = {
+        Puzzle::new(vec![0, 5, 1, 3, 8, 4, 2, 11, 12, 10, 7, 6, 9, 13, 15, 14], 4, 4).unwrap()
     };
-    static ref PUZZLE_4X4_20: (Vec<u8>, usize, usize) = {
-        (vec![1, 2, 15, 5, 0, 9, 4, 3, 12, 10, 7, 6, 13, 8, 14, 11], 4, 4)
+    static ref PUZZLE_4X4_20: Puzzle = {
+        Puzzle::new(vec![1, 2, 15, 5, 0, 9, 4, 3, 12, 10, 7, 6, 13, 8, 14, 11], 4, 4).unwrap()
     };
+    static ref PUZZLE_10X10: Puzzle = shuffled_puzzle(10, 10, 100_000, 0);
 }
 
 const MAX_NUM_STEPS_OPTIMAL: usize = 2_000_000;
 
-fn criterion_benchmark(c: &mut Criterion) {
-    let mut group100 = c.benchmark_group("100 samples");
-    group100.sample_size(100);
+const BEAM_WIDTHS: [usize; 5] = [1, 4, 16, 64, 256];
 
-    // 9.8k evaluations
-    group100.bench_function("3x3 16 steps optimal", |b| {
-        b.iter(|| {
-            find_swap_order(
-                black_box(&PUZZLE_3X3_16.0),
-                black_box(PUZZLE_3X3_16.1),
-                black_box(PUZZLE_3X3_16.2),
-                MAX_NUM_STEPS_OPTIMAL,
-            )
-        })
-    });
+/// Shuffle a solved puzzle with a seeded random walk of the empty field.
+fn shuffled_puzzle(width: usize, height: usize, num_swaps: usize, seed: u64) -> Puzzle {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut fields: Vec<u8> = (0..(width * height) as u8).collect();
+    let mut empty_idx = fields.len() - 1;
+    for _ in 0..num_swaps {
+        let (row, col) = (empty_idx / width, empty_idx % width);
+        let mut neighbours = Vec::with_capacity(4);
+        if row > 0 {
+            neighbours.push(empty_idx - width);
+        }
+        if row + 1 < height {
+            neighbours.push(empty_idx + width);
+        }
+        if col > 0 {
+            neighbours.push(empty_idx - 1);
+        }
+        if col + 1 < width {
+            neighbours.push(empty_idx + 1);
+        }
+        let neighbour_idx = *neighbours.choose(&mut rng).unwrap();
+        fields.swap(empty_idx, neighbour_idx);
+        empty_idx = neighbour_idx;
+    }
+    Puzzle::new(fields, width, height).unwrap()
+}
 
-    // 2.6k evaluations
-    group100.bench_function("4x4 10 steps optimal", |b| {
-        b.iter(|| {
-            find_swap_order(
-                black_box(&PUZZLE_4X4_10.0),
-                black_box(PUZZLE_4X4_10.1),
-                black_box(PUZZLE_4X4_10.2),
-                MAX_NUM_STEPS_OPTIMAL,
-            )
-        })
-    });
+/// Benchmark the solvers with the given names on a puzzle.
+fn bench_solvers<M: criterion::measurement::Measurement>(
+    group: &mut BenchmarkGroup<M>,
+    registry: &SolverRegistry,
+    label: &str,
+    puzzle: &Puzzle,
+    solver_names: &[&str],
+) {
+    for name in solver_names {
+        let solver = registry.get(name).unwrap();
+        assert!(solver.info().supports(puzzle.width, puzzle.height));
 
-    group100.bench_function("3x3 16 steps divide and conquer", |b| {
-        b.iter(|| {
-            let mut solver = DacPuzzleSolver::new(
-                black_box(&PUZZLE_3X3_16.0),
-                black_box(PUZZLE_3X3_16.1 as i32),
-                black_box(PUZZLE_3X3_16.2 as i32),
-            )
-            .unwrap();
-            solver.solve_puzzle().unwrap();
+        group.bench_function(format!("{label} {name}"), |b| {
+            b.iter(|| solver.solve(black_box(puzzle)).unwrap())
         });
-    });
+    }
+}
 
-    group100.bench_function("4x4 10 steps divide and conquer", |b| {
-        b.iter(|| {
-            let mut solver = DacPuzzleSolver::new(
-                black_box(&PUZZLE_4X4_10.0),
-                black_box(PUZZLE_4X4_10.1 as i32),
-                black_box(PUZZLE_4X4_10.2 as i32),
-            )
-            .unwrap();
-            solver.solve_puzzle().unwrap();
-        });
+/// Print the number of states IDA* expands with every heuristic on a puzzle.
+fn report_heuristic_expansions(label: &str, puzzle: &Puzzle) {
+    let (width, height) = (puzzle.width, puzzle.height);
+    let heuristics: [(&str, Box<dyn Heuristic>); 5] = [
+        ("manhattan", Box::new(ManhattanDistance::new(width, height))),
+        (
+            "linear conflict",
+            Box::new(LinearConflict::new(width, height)),
+        ),
+        ("corner tiles", Box::new(CornerTiles::new(width, height))),
+        ("last moves", Box::new(LastMoves::new(width, height))),
+        (
+            "combined",
+            Box::new(Max(
+                Max(
+                    LinearConflict::new(width, height),
+                    CornerTiles::new(width, height),
+                ),
+                LastMoves::new(width, height),
+            )),
+        ),
+    ];
+    for (name, heuristic) in heuristics {
+        let outcome = ida_star::find_swap_order_with_heuristic(
+            &puzzle.fields,
+            width,
+            height,
+            MAX_NUM_STEPS_OPTIMAL,
+            &heuristic,
+        )
+        .unwrap();
+        println!(
+            "{label}: IDA* with {name} expands {} states",
+            outcome.num_expanded
+        );
+    }
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let registry = SolverRegistry::new(SolverConfig {
+        max_num_steps: MAX_NUM_STEPS_OPTIMAL,
+        ..Default::default()
     });
+
+    let mut group100 = c.benchmark_group("100 samples");
+    group100.sample_size(100);
+
+    // bfs: 9.8k evaluations
+    bench_solvers(
+        &mut group100,
+        &registry,
+        "3x3 16 steps",
+        &PUZZLE_3X3_16,
+        &["lookup-table", "bfs", "divide-and-conquer"],
+    );
+
+    // bfs: 2.6k evaluations
+    bench_solvers(
+        &mut group100,
+        &registry,
+        "4x4 10 steps",
+        &PUZZLE_4X4_10,
+        &["bfs", "ida-star", "divide-and-conquer"],
+    );
     group100.finish();
 
     let mut group_slow = c.benchmark_group("10 samples");
     group_slow.sample_size(10);
 
-    // // 642k evaluations
-    group_slow.bench_function("4x4_17_steps optimal", |b| {
+    // bfs: 642k evaluations
+    bench_solvers(
+        &mut group_slow,
+        &registry,
+        "4x4 17 steps",
+        &PUZZLE_4X4_17,
+        &[
+            "bfs",
+            "bidirectional",
+            "ida-star",
+            "weighted-a-star",
+            "divide-and-conquer",
+        ],
+    );
+    #[cfg(feature = "parallel")]
+    bench_solvers(
+        &mut group_slow,
+        &registry,
+        "4x4 17 steps",
+        &PUZZLE_4X4_17,
+        &["parallel-ida-star"],
+    );
+
+    // Report the length of the optimized solution to compare against optimal
+    let dac_swaps = registry
+        .get("divide-and-conquer")
+        .unwrap()
+        .solve(&PUZZLE_4X4_17)
+        .unwrap()
+        .swaps;
+    let outcome = peephole::optimize(
+        &PUZZLE_4X4_17.fields,
+        4,
+        4,
+        &dac_swaps,
+        peephole::DEFAULT_WINDOW_SIZE,
+    )
+    .unwrap();
+    println!(
+        "4x4 17 steps: divide and conquer takes {} swaps, {} after peephole optimization",
+        dac_swaps.len(),
+        outcome.swaps.len()
+    );
+
+    group_slow.bench_function("4x4 17 steps peephole", |b| {
         b.iter(|| {
-            find_swap_order(
-                black_box(&PUZZLE_4X4_17.0),
-                black_box(PUZZLE_4X4_17.1),
-                black_box(PUZZLE_4X4_17.2),
-                MAX_NUM_STEPS_OPTIMAL,
+            peephole::optimize(
+                black_box(&PUZZLE_4X4_17.fields),
+                4,
+                4,
+                black_box(&dac_swaps),
+                peephole::DEFAULT_WINDOW_SIZE,
             )
+            .unwrap()
         })
     });
 
-    group_slow.bench_function("4x4 17 steps divide and conquer", |b| {
-        b.iter(|| {
-            let mut solver = DacPuzzleSolver::new(
-                black_box(&PUZZLE_4X4_17.0),
-                black_box(PUZZLE_4X4_17.1 as i32),
-                black_box(PUZZLE_4X4_17.2 as i32),
-            )
-            .unwrap();
-            solver.solve_puzzle().unwrap();
+    // Report the expanded states to compare the heuristics
+    report_heuristic_expansions("4x4 17 steps", &PUZZLE_4X4_17);
+    report_heuristic_expansions("4x4 20 steps", &PUZZLE_4X4_20);
+
+    bench_solvers(
+        &mut group_slow,
+        &registry,
+        "4x4 20 steps",
+        &PUZZLE_4X4_20,
+        &["ida-star", "weighted-a-star"],
+    );
+    #[cfg(feature = "parallel")]
+    bench_solvers(
+        &mut group_slow,
+        &registry,
+        "4x4 20 steps",
+        &PUZZLE_4X4_20,
+        &["parallel-ida-star"],
+    );
+
+    // Report the solution lengths to chart quality against beam width
+    let puzzle = &*PUZZLE_10X10;
+    let dac_swaps = DacPuzzleSolver::new(&puzzle.fields, 10, 10)
+        .unwrap()
+        .solve_puzzle()
+        .unwrap();
+    println!("10x10: divide and conquer takes {} swaps", dac_swaps.len());
+    for beam_width in BEAM_WIDTHS {
+        let swaps = beam_search::find_swap_order(&puzzle.fields, 10, 10, beam_width).unwrap();
+        println!("10x10: beam width {beam_width} takes {} swaps", swaps.len());
+    }
+
+    bench_solvers(
+        &mut group_slow,
+        &registry,
+        "10x10",
+        puzzle,
+        &["divide-and-conquer"],
+    );
+    for beam_width in BEAM_WIDTHS {
+        group_slow.bench_function(format!("10x10 beam width {beam_width}"), |b| {
+            b.iter(|| {
+                beam_search::find_swap_order(black_box(&puzzle.fields), 10, 10, beam_width).unwrap()
+            })
         });
-    });
+    }
     group_slow.finish();
 }
 
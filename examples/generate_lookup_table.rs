@@ -0,0 +1,31 @@
+//! Generate a lookup table file with the distances of all states of a board
+//!
+//! Usage:
+//! cargo run --release --example generate_lookup_table -- \
+//!     <width> <height> <output path>
+//!
+//! The table of the 3x3 board is embedded into the binary:
+//! cargo run --release --example generate_lookup_table -- \
+//!     3 3 assets/lookup_table_3x3.bin
+//!
+
+use slide_puzzle::{solver::lookup_table::LookupTable, Error};
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() != 3 {
+        return Err("usage: generate_lookup_table <width> <height> <output path>".into());
+    }
+
+    let width: usize = args[0].parse()?;
+    let height: usize = args[1].parse()?;
+    let output_path = &args[2];
+
+    let table = LookupTable::build(width, height)?;
+    let bytes = table.to_bytes();
+    std::fs::write(output_path, &bytes)?;
+
+    println!("Wrote {} bytes to {}", bytes.len(), output_path);
+
+    Ok(())
+}
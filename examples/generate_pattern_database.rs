@@ -0,0 +1,44 @@
+//! Generate a pattern database file
+//!
+//! Usage:
+//! cargo run --release --example generate_pattern_database -- \
+//!     <width> <height> <output path> <pattern> [<pattern> ...]
+//!
+//! Every pattern is a comma-separated list of field IDs, e.g. the 6-6-3
+//! partition of the 4x4 puzzle:
+//! cargo run --release --example generate_pattern_database -- \
+//!     4 4 www/pattern_database_4x4.bin 0,1,2,4,5,8 3,6,7,10,11,14 9,12,13
+//!
+
+use slide_puzzle::{solver::pattern_database::PatternDatabase, Error};
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() < 4 {
+        return Err(
+            "usage: generate_pattern_database <width> <height> <output path> <pattern>...".into(),
+        );
+    }
+
+    let width: usize = args[0].parse()?;
+    let height: usize = args[1].parse()?;
+    let output_path = &args[2];
+    let partition = args[3..]
+        .iter()
+        .map(|pattern| {
+            pattern
+                .split(',')
+                .map(|id| id.trim().parse::<u8>())
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let partition: Vec<&[u8]> = partition.iter().map(Vec::as_slice).collect();
+
+    let database = PatternDatabase::build(width, height, &partition)?;
+    let bytes = database.to_bytes();
+    std::fs::write(output_path, &bytes)?;
+
+    println!("Wrote {} bytes to {}", bytes.len(), output_path);
+
+    Ok(())
+}
@@ -1,3 +1,5 @@
+use std::{fmt::Debug, hash::Hash};
+
 use rand::prelude::SliceRandom;
 
 use crate::error::LibError;
@@ -20,10 +22,11 @@ impl Board {
         }
     }
 
-    pub(crate) fn init(&mut self, size: usize) {
+    pub(crate) fn init(&mut self, size: usize) -> Result<(), LibError> {
         let num_elements = size * size;
-        self.fields = (0..(num_elements as u8)).collect();
+        self.fields = initialize_fields(num_elements)?;
         self.id2idx = (0..num_elements).collect();
+        Ok(())
     }
 
     pub(crate) fn fields(&self) -> &Vec<u8> {
@@ -50,6 +53,44 @@ impl Board {
     }
 }
 
+/// Integer type of the field IDs.
+///
+/// `u8` holds the IDs of boards with up to 256 fields, e.g. 16x16, which is
+/// plenty for all solvers but divide and conquer. Larger boards need `u16`.
+pub trait TileId: Copy + Debug + Default + Eq + Ord + Hash + Send + Sync + 'static {
+    /// Number of distinct field IDs of the type.
+    const MAX_NUM_FIELDS: usize;
+
+    /// Convert an ID, `None` if it does not fit into the type.
+    fn from_usize(value: usize) -> Option<Self>;
+
+    fn to_usize(self) -> usize;
+}
+
+impl TileId for u8 {
+    const MAX_NUM_FIELDS: usize = u8::MAX as usize + 1;
+
+    fn from_usize(value: usize) -> Option<Self> {
+        u8::try_from(value).ok()
+    }
+
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
+impl TileId for u16 {
+    const MAX_NUM_FIELDS: usize = u16::MAX as usize + 1;
+
+    fn from_usize(value: usize) -> Option<Self> {
+        u16::try_from(value).ok()
+    }
+
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
 /// Coordinates consisting of row and column.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub(crate) struct Coords<T> {
@@ -111,23 +152,209 @@ where
 }
 
 /// Initialize fields as vector.
-pub(crate) fn initialize_fields(num_elements: usize) -> Vec<u8> {
-    let num_elements = usize::min(num_elements, u8::MAX as usize) as u8;
-    (0..num_elements).collect()
+///
+/// Returns [`LibError::TooManyFields`] if the IDs do not fit into the tile ID
+/// type.
+pub(crate) fn initialize_fields<T: TileId>(num_elements: usize) -> Result<Vec<T>, LibError> {
+    (0..num_elements)
+        .map(T::from_usize)
+        .collect::<Option<_>>()
+        .ok_or(LibError::TooManyFields {
+            num_fields: num_elements,
+            max_num_fields: T::MAX_NUM_FIELDS,
+        })
+}
+
+/// Get the ID of the empty field of a board, which is the largest one.
+///
+/// Returns [`LibError::TooManyFields`] if the ID does not fit into the tile ID
+/// type.
+pub(crate) fn get_empty_field_id<T: TileId>(num_fields: usize) -> Result<T, LibError> {
+    T::from_usize(num_fields.saturating_sub(1)).ok_or(LibError::TooManyFields {
+        num_fields,
+        max_num_fields: T::MAX_NUM_FIELDS,
+    })
 }
 
 /// Get the index of a value in a slice.
 ///
 /// This is a convenience wrapper which should not be used in a hot path.
-pub(crate) fn get_idx_of_val(slice: &[u8], value: u8) -> Result<usize, LibError> {
+pub(crate) fn get_idx_of_val<T: TileId>(slice: &[T], value: T) -> Result<usize, LibError> {
     slice
         .iter()
         .position(|&v| v == value)
-        .ok_or(LibError::ValueNotFound(value))
+        .ok_or(LibError::ValueNotFound(value.to_usize()))
 }
 
-pub(crate) fn get_empty_field_idx(fields: &[u8]) -> Result<usize, LibError> {
-    get_idx_of_val(fields, fields.len() as u8 - 1)
+/// Get the index of the empty field, which has the largest ID.
+///
+/// Returns [`LibError::TooManyFields`] if the largest ID does not fit into
+/// the tile ID type.
+pub(crate) fn get_empty_field_idx<T: TileId>(fields: &[T]) -> Result<usize, LibError> {
+    get_idx_of_val(fields, get_empty_field_id(fields.len())?)
+}
+
+/// Check whether the `goal` state can be reached from the `fields` state.
+///
+/// Every swap is a transposition of two fields which moves the empty field by
+/// one position. The parity of the permutation between both states therefore
+/// has to match the parity of the distance between their empty fields.
+///
+/// On a board of a single row or column, the fields besides the empty field
+/// can never change their order, so they have to be in the same order in both
+/// states.
+pub(crate) fn is_goal_reachable<T: TileId>(
+    fields: &[T],
+    goal: &[T],
+    width: usize,
+) -> Result<bool, LibError> {
+    if fields.len() != goal.len() {
+        return Err(LibError::FieldsBoardMismatch {
+            len: goal.len(),
+            expected: fields.len() as i32,
+        });
+    }
+
+    if width == 1 || width == fields.len() {
+        let empty_field_val = get_empty_field_id(fields.len())?;
+        return Ok(fields
+            .iter()
+            .filter(|&&value| value != empty_field_val)
+            .eq(goal.iter().filter(|&&value| value != empty_field_val)));
+    }
+
+    // Goal index of every field value
+    let mut goal_positions = vec![usize::MAX; goal.len()];
+    for (idx, &value) in goal.iter().enumerate() {
+        if let Some(goal_idx) = goal_positions.get_mut(value.to_usize()) {
+            *goal_idx = idx;
+        }
+    }
+
+    // Map every index to the goal index of its field
+    let permutation = fields
+        .iter()
+        .map(|&value| match goal_positions.get(value.to_usize()) {
+            Some(&goal_idx) if goal_idx != usize::MAX => Ok(goal_idx),
+            _ => Err(LibError::ValueNotFound(value.to_usize())),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // A permutation is even if the number of fields minus the number of its
+    // cycles is even
+    let mut visited = vec![false; permutation.len()];
+    let mut num_cycles = 0;
+    for start_idx in 0..permutation.len() {
+        if visited[start_idx] {
+            continue;
+        }
+        num_cycles += 1;
+
+        let mut idx = start_idx;
+        while !visited[idx] {
+            visited[idx] = true;
+            idx = permutation[idx];
+        }
+    }
+    let permutation_parity = (permutation.len() - num_cycles) % 2;
+
+    let (row, col) = get_row_col_from_idx(get_empty_field_idx(fields)?, width);
+    let (goal_row, goal_col) = get_row_col_from_idx(get_empty_field_idx(goal)?, width);
+    let distance_parity = (row.abs_diff(goal_row) + col.abs_diff(goal_col)) % 2;
+
+    Ok(permutation_parity == distance_parity)
+}
+
+/// Parity analysis of a puzzle state with respect to the solved state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Solvability {
+    /// Number of pairs of fields, excluding the empty field, which are in the
+    /// wrong order when read row by row.
+    pub num_inversions: usize,
+    /// Row of the empty field counted from the bottom row, starting at 0.
+    pub empty_field_row_from_bottom: usize,
+    /// Whether the solved state can be reached.
+    pub solvable: bool,
+}
+
+/// Analyze whether a puzzle state can be solved.
+///
+/// A horizontal swap does not change the reading order of the fields. A
+/// vertical swap moves one field past the `width - 1` fields in between, which
+/// changes the number of inversions by an odd amount for even widths and by an
+/// even amount for odd widths. Since the empty field is in the bottom row of
+/// the solved state, the state is solvable if the number of inversions is even
+/// for odd widths, and if the number of inversions plus the row of the empty
+/// field from the bottom is even for even widths.
+///
+/// On boards of a single row or column, the empty field only moves along the
+/// line, so only states without inversions are solvable.
+pub fn analyze_solvability<T: TileId>(
+    fields: &[T],
+    width: usize,
+    height: usize,
+) -> Result<Solvability, LibError> {
+    if fields.len() != width * height {
+        return Err(LibError::FieldsBoardMismatch {
+            len: fields.len(),
+            expected: (width * height) as i32,
+        });
+    }
+
+    // The parity is only meaningful for a permutation of all field IDs
+    let mut seen = vec![false; fields.len()];
+    for &value in fields {
+        if let Some(seen) = seen.get_mut(value.to_usize()) {
+            *seen = true;
+        }
+    }
+    if let Some(missing) = seen.iter().position(|&seen| !seen) {
+        return Err(LibError::ValueNotFound(missing));
+    }
+
+    let empty_field_idx = get_empty_field_idx(fields)?;
+    let tiles: Vec<T> = fields
+        .iter()
+        .copied()
+        .filter(|&value| value.to_usize() != fields.len() - 1)
+        .collect();
+    let num_inversions: usize = tiles
+        .iter()
+        .enumerate()
+        .map(|(idx, &value)| {
+            tiles[idx + 1..]
+                .iter()
+                .filter(|&&other| other < value)
+                .count()
+        })
+        .sum();
+
+    let (empty_field_row, _) = get_row_col_from_idx(empty_field_idx, width);
+    let empty_field_row_from_bottom = height - 1 - empty_field_row;
+    let solvable = match (width, height) {
+        // The fields of a single line can never change their order
+        (1, _) | (_, 1) => num_inversions == 0,
+        _ if width % 2 == 1 => num_inversions.is_multiple_of(2),
+        _ => (num_inversions + empty_field_row_from_bottom).is_multiple_of(2),
+    };
+
+    Ok(Solvability {
+        num_inversions,
+        empty_field_row_from_bottom,
+        solvable,
+    })
+}
+
+/// Return [`LibError::Unsolvable`] if the solved state cannot be reached.
+pub(crate) fn ensure_solvable<T: TileId>(
+    fields: &[T],
+    width: usize,
+    height: usize,
+) -> Result<(), LibError> {
+    match analyze_solvability(fields, width, height)?.solvable {
+        true => Ok(()),
+        false => Err(LibError::Unsolvable),
+    }
 }
 
 /// Get the indices of neighbours that can be swapped with the empty field.
@@ -1,17 +1,81 @@
 #[derive(thiserror::Error, Debug)]
 pub enum LibError {
     #[error("value {0} not found")]
-    ValueNotFound(u8),
+    ValueNotFound(usize),
     #[error("pos (row: {row}, col: {col}) out of bounds")]
     PosOutOfBounds { row: i32, col: i32 },
+    #[error("goal state cannot be reached from the initial state")]
+    GoalUnreachable,
+    #[error("puzzle cannot be solved, the parity of the fields does not match the empty field")]
+    Unsolvable,
+    #[error("no puzzle with an optimal distance between {min} and {max} swaps found")]
+    NoPuzzleAtDistance { min: usize, max: usize },
     #[error("algorithm terminated without finding a solution")]
     TerminatedWithoutSolution,
     #[error("maximum number of steps ({0}) reached without finding a solution")]
     MaxNumStepsReached(usize),
-    #[error("board is not square with width {width} and height {height}")]
-    NotSquare { width: i32, height: i32 },
     #[error("fields slice with {len} fields does not match board expecting {expected} fields")]
     FieldsBoardMismatch { len: usize, expected: i32 },
-    #[error("boards below 3x3 are not supported")]
-    Below3x3,
+    #[error("swap {step} {swap:?} is invalid: {reason}")]
+    InvalidSwap {
+        step: usize,
+        swap: (usize, usize),
+        reason: InvalidSwapReason,
+    },
+    #[error("invalid notation at position {position}: {reason}")]
+    InvalidNotation {
+        position: usize,
+        reason: &'static str,
+    },
+    #[error(
+        "swap {step} ({direction}) is illegal with the empty field at index {empty_field_idx}"
+    )]
+    IllegalMove {
+        step: usize,
+        direction: char,
+        empty_field_idx: usize,
+    },
+    #[error("fields are not solved after {num_swaps} swaps, {num_misplaced} fields are misplaced")]
+    NotSolved {
+        num_swaps: usize,
+        num_misplaced: usize,
+    },
+    #[error("epsilon {0} is not a finite, non-negative number")]
+    InvalidEpsilon(f64),
+    #[error("solver was cancelled")]
+    Cancelled,
+    #[error("board of size {width}x{height} is too small, it needs at least two rows and columns")]
+    BoardTooSmall { width: usize, height: usize },
+    #[error("{num_fields} fields exceed the {max_num_fields} field IDs of the tile ID type")]
+    TooManyFields {
+        num_fields: usize,
+        max_num_fields: usize,
+    },
+    #[error("board of size {width}x{height} is not supported")]
+    UnsupportedSize { width: usize, height: usize },
+    #[error("invalid pattern database: {0}")]
+    InvalidPatternDatabase(&'static str),
+    #[error("unsupported pattern database version {0}")]
+    UnsupportedPatternDatabaseVersion(u16),
+    #[error("checksum mismatch, expected {expected:#018x} but computed {actual:#018x}")]
+    ChecksumMismatch { expected: u64, actual: u64 },
+    #[error("field {0} cannot be part of a pattern")]
+    InvalidPatternField(u8),
+    #[error("field {0} is part of more than one pattern")]
+    DuplicatePatternField(u8),
+    #[error("invalid lookup table: {0}")]
+    InvalidLookupTable(&'static str),
+    #[error("unsupported lookup table version {0}")]
+    UnsupportedLookupTableVersion(u16),
+}
+
+/// Reason why a swap of a swap sequence is invalid.
+#[derive(thiserror::Error, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidSwapReason {
+    #[error("index {0} is out of bounds")]
+    OutOfBounds(usize),
+    #[error("the empty field is at index {empty_field_idx}")]
+    NotEmptyField { empty_field_idx: usize },
+    #[error("the fields are not orthogonal neighbours")]
+    NotNeighbour,
 }
@@ -1,8 +1,9 @@
 //! Slide puzzle frontend and solvers.
 //!
 
-use std::cell::RefCell;
+use std::{cell::RefCell, sync::Arc};
 
+use solver::pattern_database::PatternDatabase;
 use ui::{
     board::UiBoard, buttons::setup_button_callbacks, search_params::extract_parameters,
     set_panic_hook, touch::TouchCoords, unlock_ui,
@@ -20,6 +21,7 @@ thread_local! {
     static UI_LOCKED: RefCell<bool> = const { RefCell::new(true) };
     static BOARD: RefCell<UiBoard> = const { RefCell::new(UiBoard::new()) };
     static TOUCH_COORDS: RefCell<TouchCoords> = const { RefCell::new(TouchCoords::new()) };
+    static PATTERN_DATABASE: RefCell<Option<Arc<PatternDatabase>>> = const { RefCell::new(None) };
 }
 
 #[wasm_bindgen]
@@ -32,11 +34,28 @@ pub fn wasm_main() {
     let params = extract_parameters();
     log::debug!("Params: {:?}", params);
 
-    setup_button_callbacks(params.size);
+    setup_button_callbacks(params.size, params.metric, params.difficulty);
 
-    BOARD.with_borrow_mut(|b| {
-        b.init(params);
-    });
+    // The UI stores `u8` field IDs, so boards larger than 16x16 are rejected
+    if let Err(err) = BOARD.with_borrow_mut(|b| b.init(params)) {
+        log::error!("failed to initialize board: {err}");
+        return;
+    }
 
     unlock_ui();
 }
+
+/// Load a prebuilt pattern database to speed up optimal solving.
+#[wasm_bindgen]
+pub fn load_pattern_database(bytes: &[u8]) -> Result<(), JsError> {
+    let database = PatternDatabase::from_bytes(bytes)?;
+    log::info!(
+        "Loaded pattern database for {}x{} boards",
+        database.width(),
+        database.height()
+    );
+
+    PATTERN_DATABASE.with_borrow_mut(|d| *d = Some(Arc::new(database)));
+
+    Ok(())
+}
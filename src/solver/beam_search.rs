@@ -0,0 +1,482 @@
+//! Beam search puzzle solver for large boards
+//!
+//! The solver follows the decomposition of the divide and conquer solver (see
+//! [`DacPuzzleSolver`]) and solves one row or column after another. Instead of
+//! moving the fields of a line one by one with fixed routines, a beam search
+//! looks for a short swap sequence which puts all fields of the line in place.
+//!
+//! The beam search expands the states level by level like a breadth-first
+//! search, but only keeps the `beam_width` most promising states of every
+//! level. States are scored by the Manhattan distances of the fields of the
+//! line to their goal positions and the distance of the empty field to the
+//! next field to place. Time and memory are therefore bounded by the beam width
+//! and the search depth.
+//!
+//! Every line is solved by the divide and conquer solver first. Its number of
+//! swaps limits the depth of the beam search, and its swaps are used if the
+//! beam search does not find a shorter sequence. If the beam search only
+//! places the first fields of a line, the divide and conquer routines continue
+//! from there. The result is never longer than the divide and conquer
+//! solution, but typically only 10-20% shorter.
+//!
+//! The solution length is not monotone in the beam width. A wider beam
+//! usually finds a shorter sequence for a line, but may leave the remaining
+//! fields in a worse position for the next lines. On some boards, a
+//! width of 64 therefore takes fewer swaps than a width of 256.
+//!
+
+use std::hash::{Hash, Hasher};
+
+use rustc_hash::{FxHashSet, FxHasher};
+
+use crate::{
+    board::{
+        get_coords_from_idx, get_idx_from_coords, get_row_col_from_idx, get_swappable_neighbours,
+        Coords,
+    },
+    error::LibError,
+    solver::{
+        divide_and_conquer::DacPuzzleSolver,
+        move_metric::MoveMetric,
+        progress::{NoProgress, ProgressObserver, ProgressReporter},
+        registry::{Puzzle, Solution, Solver, SolverInfo},
+    },
+};
+
+/// Beam width which balances solution length and speed on boards up to 15x15.
+pub const DEFAULT_BEAM_WIDTH: usize = 64;
+
+/// Weight of the Manhattan distances of the fields of the line in the score.
+///
+/// Moving a field by one position takes about five swaps of the empty field,
+/// so bringing a field closer has to outweigh moving the empty field away.
+const FIELD_DISTANCE_WEIGHT: usize = 5;
+
+/// Find the swap order to solve a puzzle with a beam search per row and
+/// column.
+///
+/// A beam width of zero falls back to the divide and conquer solver for every
+/// line.
+pub fn find_swap_order(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    beam_width: usize,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    find_swap_order_with_progress(fields, width, height, beam_width, &mut NoProgress)
+}
+
+/// Find the swap order with a beam search and report the progress after every
+/// solved row and column.
+///
+/// The progress holds the number of solved fields and swaps so far.
+pub fn find_swap_order_with_progress(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    beam_width: usize,
+    observer: &mut dyn ProgressObserver,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    let mut solver = DacPuzzleSolver::new(fields, width as i32, height as i32)?;
+    let mut reporter = ProgressReporter::new(observer, 1);
+
+    while let Some(line) = solver.next_line() {
+        // Solve the line and its corner with the divide and conquer routines,
+        // which bounds the depth of the beam search
+        let mut fallback = solver.clone();
+        fallback.solve_next_stage()?;
+        fallback.solve_next_stage()?;
+        let num_fallback_swaps = fallback.swaps().len() - solver.swaps().len();
+
+        let search = LineSearch::new(&solver, &line, width, height);
+        let outcome = search.find(
+            solver.fields(),
+            beam_width,
+            num_fallback_swaps.saturating_sub(1),
+        );
+        if outcome.complete {
+            solver.apply_line_swaps(&outcome.swaps);
+        } else {
+            // Continue with the divide and conquer routines from the fields
+            // placed by the beam search if that is shorter
+            let mut partial = solver.clone();
+            partial.apply_swaps(&outcome.swaps);
+            partial.solve_next_stage()?;
+            partial.solve_next_stage()?;
+            solver = match partial.swaps().len() < fallback.swaps().len() {
+                true => partial,
+                false => fallback,
+            };
+        }
+
+        reporter.update(solver.num_fixed_fields(), solver.swaps().len())?;
+    }
+
+    // Solve the last 2x2 square
+    while solver.solve_next_stage()?.is_some() {}
+
+    // Solving a line differently changes the following lines, which are
+    // rarely but occasionally longer than those of the divide and conquer
+    // solver
+    let dac_swaps = DacPuzzleSolver::new(fields, width as i32, height as i32)?.solve_puzzle()?;
+    let swaps = match solver.swaps().len() <= dac_swaps.len() {
+        true => solver.swaps().to_vec(),
+        false => dac_swaps,
+    };
+
+    log::debug!("Number of swaps to solve: {}", swaps.len());
+
+    Ok(swaps)
+}
+
+/// Beam search solver, see [`find_swap_order`].
+#[derive(Clone, Copy, Debug)]
+pub struct BeamSearch {
+    pub beam_width: usize,
+}
+
+impl Default for BeamSearch {
+    fn default() -> Self {
+        Self {
+            beam_width: DEFAULT_BEAM_WIDTH,
+        }
+    }
+}
+
+impl Solver for BeamSearch {
+    fn info(&self) -> SolverInfo {
+        SolverInfo {
+            name: "beam-search",
+            optimal: false,
+            metric: MoveMetric::SingleTile,
+            square_only: false,
+            min_size: 2,
+            // The field IDs of larger boards do not fit into `u8`
+            max_num_fields: 256,
+        }
+    }
+
+    fn solve_with_progress(
+        &self,
+        puzzle: &Puzzle,
+        observer: &mut dyn ProgressObserver,
+    ) -> Result<Solution, LibError> {
+        let swaps = find_swap_order_with_progress(
+            &puzzle.fields,
+            puzzle.width,
+            puzzle.height,
+            self.beam_width,
+            observer,
+        )?;
+        Ok(Solution { swaps })
+    }
+}
+
+/// Beam search for the swaps which solve a single row or column.
+///
+/// The fields of the line are placed one after another in the order of the
+/// divide and conquer solver. Every step is a subgoal of its own beam search,
+/// which starts from the state reaching the previous subgoal. Fields placed
+/// before may move during a search as long as they are back in place at its
+/// end.
+///
+/// The last field of a line cannot be placed directly without moving a placed
+/// field. Like the divide and conquer solver, we therefore place the second
+/// last field at the end of the line and the last field next to it outside of
+/// the line first, from where both fields move into place with two swaps.
+struct LineSearch {
+    width: usize,
+    height: usize,
+    /// Whether the empty field may move to an index, i.e. the index is not
+    /// part of an already solved row or column.
+    movable: Vec<bool>,
+    /// Index into the fields of the line for every field value.
+    line_field_of_value: Vec<Option<usize>>,
+    /// Targets of the fields of the line for every subgoal, as pairs of the
+    /// index into the fields of the line and the target index on the board.
+    subgoals: Vec<Vec<(usize, usize)>>,
+}
+
+/// Swaps found by [`LineSearch::find`].
+struct LineOutcome {
+    swaps: Vec<(usize, usize)>,
+    /// Whether the swaps solve the whole line, otherwise they only place some
+    /// of its first fields.
+    complete: bool,
+}
+
+/// State in the beam.
+#[derive(Clone)]
+struct Candidate {
+    fields: Vec<u8>,
+    empty_idx: usize,
+    /// Number of swaps from the initial state.
+    depth: usize,
+    /// Index of the last swap in the swap tree, `None` for the initial state.
+    node: Option<usize>,
+    /// Current index of every field of the line.
+    line_indices: Vec<usize>,
+}
+
+/// Swap leading to a state in the beam, linked to the swap before.
+struct Node {
+    parent: Option<usize>,
+    swap: (usize, usize),
+}
+
+/// Expansion of a candidate which is only materialized if it is kept.
+struct Child {
+    score: usize,
+    candidate_idx: usize,
+    neighbour_idx: usize,
+}
+
+impl LineSearch {
+    fn new(solver: &DacPuzzleSolver, line: &[Coords<i32>], width: usize, height: usize) -> Self {
+        let to_idx = |pos: Coords<i32>| get_idx_from_coords(pos, width as i32) as usize;
+
+        let movable = (0..width * height)
+            .map(|idx| !solver.is_solved_pos(get_coords_from_idx(idx as i32, width as i32)))
+            .collect();
+
+        let mut line_field_of_value = vec![None; width * height];
+        for (line_field, &pos) in line.iter().enumerate() {
+            line_field_of_value[solver.goal_array()[to_idx(pos)] as usize] = Some(line_field);
+        }
+
+        // Place all fields but the last two one by one
+        let goals: Vec<(usize, usize)> = line.iter().map(|&pos| to_idx(pos)).enumerate().collect();
+        let num_fields = line.len();
+        let mut subgoals: Vec<_> = (1..num_fields - 1)
+            .map(|num_placed| goals[..num_placed].to_vec())
+            .collect();
+
+        // Place the second last field at the end of the line and the last one
+        // next to it. Rows are solved top down and columns left to right, so
+        // the position next to the end is below a row or right of a column.
+        let end = line[num_fields - 1];
+        let direction = Coords {
+            row: end.row - line[num_fields - 2].row,
+            col: end.col - line[num_fields - 2].col,
+        };
+        let next_to_end = Coords {
+            row: end.row + direction.col,
+            col: end.col + direction.row,
+        };
+        let mut setup = goals[..num_fields - 2].to_vec();
+        setup.push((num_fields - 2, to_idx(end)));
+        setup.push((num_fields - 1, to_idx(next_to_end)));
+        subgoals.push(setup);
+
+        subgoals.push(goals);
+
+        Self {
+            width,
+            height,
+            movable,
+            line_field_of_value,
+            subgoals,
+        }
+    }
+
+    /// Find at most `max_num_swaps` swaps which put all fields of the line in
+    /// place.
+    ///
+    /// If a subgoal is not reached, the swaps reaching the previous subgoal
+    /// are returned.
+    fn find(&self, fields: &[u8], beam_width: usize, max_num_swaps: usize) -> LineOutcome {
+        let empty_field_val = (fields.len() - 1) as u8;
+        let mut line_indices = vec![0; self.subgoals[self.subgoals.len() - 1].len()];
+        let mut empty_idx = 0;
+        for (idx, &value) in fields.iter().enumerate() {
+            if let Some(line_field) = self.line_field_of_value[value as usize] {
+                line_indices[line_field] = idx;
+            }
+            if value == empty_field_val {
+                empty_idx = idx;
+            }
+        }
+
+        let mut candidate = Candidate {
+            fields: fields.to_owned(),
+            empty_idx,
+            depth: 0,
+            node: None,
+            line_indices,
+        };
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut complete = true;
+
+        for targets in self.subgoals.iter() {
+            match self.find_subgoal(
+                candidate.clone(),
+                targets,
+                beam_width,
+                max_num_swaps,
+                &mut nodes,
+            ) {
+                Some(next) => candidate = next,
+                None => {
+                    complete = false;
+                    break;
+                }
+            }
+        }
+
+        let swaps = match candidate.node {
+            Some(node) => trace_back(&nodes, node),
+            None => Vec::new(),
+        };
+        LineOutcome { swaps, complete }
+    }
+
+    /// Run a beam search until all fields of the line are at their targets
+    /// and return the state reaching this subgoal.
+    fn find_subgoal(
+        &self,
+        initial: Candidate,
+        targets: &[(usize, usize)],
+        beam_width: usize,
+        max_num_swaps: usize,
+        nodes: &mut Vec<Node>,
+    ) -> Option<Candidate> {
+        if self.distance(&initial.line_indices, targets) == 0 {
+            return Some(initial);
+        }
+
+        // Hashes of all states seen so far. A collision can only hide a state
+        // from the search, which is acceptable for a heuristic search.
+        let mut seen = FxHashSet::from_iter([state_hash(&initial.fields)]);
+        let mut beam = vec![initial];
+
+        while beam[0].depth < max_num_swaps {
+            let mut children = Vec::new();
+            for (candidate_idx, candidate) in beam.iter_mut().enumerate() {
+                let empty_idx = candidate.empty_idx;
+                for neighbour_idx in get_swappable_neighbours(self.width, self.height, empty_idx) {
+                    if !self.movable[neighbour_idx] {
+                        continue;
+                    }
+
+                    candidate.fields.swap(empty_idx, neighbour_idx);
+                    let is_new = seen.insert(state_hash(&candidate.fields));
+                    candidate.fields.swap(empty_idx, neighbour_idx);
+                    if !is_new {
+                        continue;
+                    }
+
+                    children.push(Child {
+                        score: self.score(candidate, neighbour_idx, targets),
+                        candidate_idx,
+                        neighbour_idx,
+                    });
+                }
+            }
+
+            if children.len() > beam_width {
+                children.select_nth_unstable_by_key(beam_width, |child| child.score);
+                children.truncate(beam_width);
+            }
+            if children.is_empty() {
+                return None;
+            }
+
+            let mut next_beam = Vec::with_capacity(children.len());
+            for child in children {
+                let parent = &beam[child.candidate_idx];
+                nodes.push(Node {
+                    parent: parent.node,
+                    swap: (parent.empty_idx, child.neighbour_idx),
+                });
+                let next = self.expand(parent, child.neighbour_idx, nodes.len() - 1);
+
+                if self.distance(&next.line_indices, targets) == 0 {
+                    return Some(next);
+                }
+                next_beam.push(next);
+            }
+            beam = next_beam;
+        }
+
+        None
+    }
+
+    /// Score the state after swapping the empty field of a candidate with a
+    /// neighbour, lower is better.
+    ///
+    /// The score consists of the distances of the fields to their targets and
+    /// the distance of the empty field to the last field which is not at its
+    /// target. This is the field which is placed next, fields placed before
+    /// only leave their targets temporarily.
+    fn score(
+        &self,
+        candidate: &Candidate,
+        neighbour_idx: usize,
+        targets: &[(usize, usize)],
+    ) -> usize {
+        let mut distance = 0;
+        let mut empty_distance = 0;
+        for &(line_field, target_idx) in targets {
+            // The field at the neighbour index moves to the empty field index
+            let idx = match candidate.line_indices[line_field] == neighbour_idx {
+                true => candidate.empty_idx,
+                false => candidate.line_indices[line_field],
+            };
+
+            let field_distance = self.manhattan(idx, target_idx);
+            if field_distance > 0 {
+                empty_distance = self.manhattan(idx, neighbour_idx);
+            }
+            distance += field_distance;
+        }
+
+        FIELD_DISTANCE_WEIGHT * distance + empty_distance
+    }
+
+    /// Create the state after swapping the empty field of a candidate with a
+    /// neighbour.
+    fn expand(&self, candidate: &Candidate, neighbour_idx: usize, node: usize) -> Candidate {
+        let mut next = candidate.clone();
+        let moved_value = next.fields[neighbour_idx];
+        if let Some(line_field) = self.line_field_of_value[moved_value as usize] {
+            next.line_indices[line_field] = candidate.empty_idx;
+        }
+        next.fields.swap(candidate.empty_idx, neighbour_idx);
+        next.empty_idx = neighbour_idx;
+        next.depth += 1;
+        next.node = Some(node);
+        next
+    }
+
+    /// Sum of the Manhattan distances of the fields to their targets.
+    fn distance(&self, line_indices: &[usize], targets: &[(usize, usize)]) -> usize {
+        targets
+            .iter()
+            .map(|&(line_field, target_idx)| self.manhattan(line_indices[line_field], target_idx))
+            .sum()
+    }
+
+    fn manhattan(&self, idx: usize, other_idx: usize) -> usize {
+        let (row, col) = get_row_col_from_idx(idx, self.width);
+        let (other_row, other_col) = get_row_col_from_idx(other_idx, self.width);
+        row.abs_diff(other_row) + col.abs_diff(other_col)
+    }
+}
+
+/// Collect the swaps from the initial state to a node of the swap tree.
+fn trace_back(nodes: &[Node], node: usize) -> Vec<(usize, usize)> {
+    let mut swaps = Vec::new();
+    let mut next = Some(node);
+    while let Some(node) = next {
+        swaps.push(nodes[node].swap);
+        next = nodes[node].parent;
+    }
+    swaps.reverse();
+    swaps
+}
+
+fn state_hash(fields: &[u8]) -> u64 {
+    let mut hasher = FxHasher::default();
+    fields.hash(&mut hasher);
+    hasher.finish()
+}
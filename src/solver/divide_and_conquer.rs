@@ -8,20 +8,38 @@ use std::collections::{HashMap, HashSet, VecDeque};
 
 use crate::{
     board::{
-        get_coords_from_idx, get_empty_field_idx, get_idx_from_coords, get_idx_of_val, in_bounds,
-        initialize_fields, Coords,
+        ensure_solvable, get_coords_from_idx, get_empty_field_id, get_empty_field_idx,
+        get_idx_from_coords, get_idx_of_val, in_bounds, initialize_fields, is_goal_reachable,
+        Coords, TileId,
     },
     error::LibError,
+    solver::{
+        move_metric::MoveMetric,
+        progress::{NoProgress, ProgressObserver, ProgressReporter},
+        registry::{Puzzle, Solution, Solver, SolverInfo},
+    },
 };
 
-pub struct DacPuzzleSolver {
-    fields: Vec<u8>,
+/// Divide and conquer solver of boards of any size.
+///
+/// The field IDs are `u8` by default, boards with more than 256 fields need
+/// `u16` IDs.
+#[derive(Clone)]
+pub struct DacPuzzleSolver<T: TileId = u8> {
+    fields: Vec<T>,
     fixed_fields: HashSet<Coords<i32>>,
     width: i32,
     height: i32,
     empty_field_pos: Coords<i32>,
     swaps: Vec<(usize, usize)>,
-    goal_array: Vec<u8>,
+    goal_array: Vec<T>,
+    /// Moves of the empty field from the bottom right corner to its position
+    /// in the goal, applied after the last 2x2 square is solved.
+    goal_return_moves: Vec<Coords<i32>>,
+    stage: SolverStage,
+    phase: SolverPhase,
+    working_row: i32,
+    working_col: i32,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -30,9 +48,56 @@ enum SolverPhase {
     Column,
 }
 
-impl DacPuzzleSolver {
+/// Next stage of a solver, see [`DacPuzzleSolver::stages`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SolverStage {
+    /// Solve a row or column until the second last field.
+    Line,
+    /// Solve the last field of a row or column.
+    Corner,
+    /// Solve the last 2x2 square and move the empty field to its goal.
+    LastFour,
+    Done,
+}
+
+/// Iterator over the swaps of a [`DacPuzzleSolver`] stage by stage.
+pub struct DacStages<'a, T: TileId = u8> {
+    solver: &'a mut DacPuzzleSolver<T>,
+    failed: bool,
+}
+
+impl<T: TileId> Iterator for DacStages<'_, T> {
+    type Item = Result<Vec<(usize, usize)>, LibError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        match self.solver.solve_next_stage() {
+            Ok(swaps) => swaps.map(|swaps| Ok(swaps.to_vec())),
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl<T: TileId> DacPuzzleSolver<T> {
     /// Create a new solver instance.
-    pub fn new(fields: &[u8], width: i32, height: i32) -> Result<Self, LibError> {
+    ///
+    /// Returns [`LibError::Unsolvable`] if the puzzle cannot be solved, since
+    /// the last 2x2 square would be cycled forever.
+    pub fn new(fields: &[T], width: i32, height: i32) -> Result<Self, LibError> {
+        let solver = Self::new_unchecked(fields, width, height)?;
+        ensure_solvable(fields, width as usize, height as usize)?;
+        Ok(solver)
+    }
+
+    /// Create a new solver instance without checking that the solved state
+    /// can be reached.
+    fn new_unchecked(fields: &[T], width: i32, height: i32) -> Result<Self, LibError> {
         if fields.len() as i32 != width * height {
             return Err(LibError::FieldsBoardMismatch {
                 len: fields.len(),
@@ -40,125 +105,302 @@ impl DacPuzzleSolver {
             });
         }
 
-        if width != height {
-            return Err(LibError::NotSquare { width, height });
-        }
-
-        if width < 3 || height < 3 {
-            return Err(LibError::Below3x3);
+        if width < 2 || height < 2 {
+            return Err(LibError::BoardTooSmall {
+                width: width as usize,
+                height: height as usize,
+            });
         }
 
         let empty_field_idx = get_empty_field_idx(fields)? as i32;
         let empty_field_pos = get_coords_from_idx(empty_field_idx, width);
 
-        Ok(Self {
+        let mut solver = Self {
             fields: fields.to_owned(),
             fixed_fields: HashSet::new(),
             width,
             height,
             empty_field_pos,
             swaps: Vec::new(),
-            goal_array: initialize_fields((width * height) as usize),
-        })
+            goal_array: initialize_fields((width * height) as usize)?,
+            goal_return_moves: Vec::new(),
+            stage: SolverStage::Line,
+            phase: SolverPhase::Row,
+            working_row: 0,
+            working_col: 0,
+        };
+        solver.stage = solver.next_line_stage();
+
+        Ok(solver)
+    }
+
+    /// Create a new solver instance which solves towards an arbitrary goal
+    /// state instead of the solved puzzle.
+    ///
+    /// The solver always finishes with the empty field in the bottom right
+    /// corner. We therefore move the empty field of the goal along the last
+    /// row and column to this corner, solve towards the resulting state and
+    /// move the empty field back along the same path afterwards.
+    pub fn with_goal(fields: &[T], goal: &[T], width: i32, height: i32) -> Result<Self, LibError> {
+        let mut solver = Self::new_unchecked(fields, width, height)?;
+        if !is_goal_reachable(fields, goal, width as usize)? {
+            return Err(LibError::GoalUnreachable);
+        }
+
+        // Path of the empty field of the goal to the bottom right corner
+        let goal_empty_field_pos = get_coords_from_idx(get_empty_field_idx(goal)? as i32, width);
+        let mut path = vec![goal_empty_field_pos];
+        path.extend((goal_empty_field_pos.col + 1..width).map(|col| Coords {
+            row: goal_empty_field_pos.row,
+            col,
+        }));
+        path.extend((goal_empty_field_pos.row + 1..height).map(|row| Coords {
+            row,
+            col: width - 1,
+        }));
+
+        let mut goal_array = goal.to_owned();
+        for step in path.windows(2) {
+            goal_array.swap(
+                get_idx_from_coords(step[0], width) as usize,
+                get_idx_from_coords(step[1], width) as usize,
+            );
+        }
+
+        path.pop();
+        path.reverse();
+        solver.goal_array = goal_array;
+        solver.goal_return_moves = path;
+
+        Ok(solver)
     }
 
     /// Solve a slide-puzzle by finding the required swaps (empty field moves).
     pub fn solve_puzzle(&mut self) -> Result<Vec<(usize, usize)>, LibError> {
-        // We alternate phases of solving rows and columns
-        let mut phase = SolverPhase::Row;
-
-        // As working_row and working_col increase, they lock out growing parts
-        // of already ordered fields. The array below shows the order of solving
-        // a 4x4 field, E stand for end.
-        // 0 0 0 0
-        // 1 2 2 2
-        // 1 3 E E
-        // 1 3 E E
-        let mut working_row = 0;
-        let mut working_col = 0;
-
-        'row_col_loop: loop {
-            // Exit alternating row/column solving if only a square of 2x2 is
-            // left
-            if self.width - working_col < 2 || self.height - working_row < 2 {
-                break 'row_col_loop;
-            }
+        self.solve_puzzle_with_progress(&mut NoProgress)
+    }
 
-            match phase {
-                SolverPhase::Row => {
-                    // Solve fields in the row starting at `working_col` until
-                    // the second last.
-                    for col in working_col..self.width - 1 {
-                        // Position that we want to fill with the right value/field
-                        let cur_pos = Coords {
-                            row: working_row,
-                            col,
-                        };
-                        let cur_pos_value = self.value_at_pos(cur_pos)?;
-                        let cur_pos_goal_value = self.goal_value_of_pos(cur_pos)?;
-                        if cur_pos_value != cur_pos_goal_value {
-                            let goal_value_pos = self.pos_of_value(cur_pos_goal_value)?;
-                            self.swap_field_to_goal_pos(goal_value_pos, cur_pos, phase);
-                        }
-                        self.fixed_fields.insert(cur_pos);
-                    }
-                }
+    /// Solve a slide-puzzle and report the progress after every solved row,
+    /// column and corner.
+    ///
+    /// The progress holds the number of solved fields and swaps so far.
+    pub fn solve_puzzle_with_progress(
+        &mut self,
+        observer: &mut dyn ProgressObserver,
+    ) -> Result<Vec<(usize, usize)>, LibError> {
+        let mut reporter = ProgressReporter::new(observer, 1);
 
-                SolverPhase::Column => {
-                    // Solve fields in the column starting at `working_row`
-                    // until the second last.
-                    for row in working_row..self.height - 1 {
-                        // Position that we want to fill with the right value/field
-                        let cur_pos = Coords {
-                            row,
-                            col: working_col,
-                        };
-                        let cur_pos_value = self.value_at_pos(cur_pos)?;
-                        let cur_pos_goal_value = self.goal_value_of_pos(cur_pos)?;
-                        if cur_pos_value != cur_pos_goal_value {
-                            let goal_value_pos = self.pos_of_value(cur_pos_goal_value)?;
-                            self.swap_field_to_goal_pos(goal_value_pos, cur_pos, phase);
-                        }
-                        self.fixed_fields.insert(cur_pos);
-                    }
-                }
+        while self.solve_next_stage()?.is_some() {
+            reporter.update(self.fixed_fields.len(), self.swaps.len())?;
+        }
+
+        Ok(self.swaps.clone())
+    }
+
+    /// Iterate over the swaps of the solution stage by stage.
+    ///
+    /// Every item holds the swaps of one row, column, corner or the last 2x2
+    /// square and may be empty. Together, the items are exactly the swaps of
+    /// [`DacPuzzleSolver::solve_puzzle`], so a consumer can apply them before
+    /// the whole puzzle is solved or stop early.
+    pub fn stages(&mut self) -> DacStages<'_, T> {
+        DacStages {
+            solver: self,
+            failed: false,
+        }
+    }
+
+    /// Solve the next stage and return its swaps, or `None` if the puzzle is
+    /// solved.
+    ///
+    /// We solve rows and columns in stages. As `working_row` and `working_col`
+    /// increase, they lock out growing parts of already ordered fields. The
+    /// arrays below show the order of solving a 4x4 and a 3x5 field, E stand
+    /// for end.
+    /// 0 0 0 0   0 1 2 2 2
+    /// 1 2 2 2   0 1 3 E E
+    /// 1 3 E E   0 1 3 E E
+    /// 1 3 E E
+    pub(crate) fn solve_next_stage(&mut self) -> Result<Option<&[(usize, usize)]>, LibError> {
+        let num_previous_swaps = self.swaps.len();
+
+        match self.stage {
+            SolverStage::Line => {
+                self.solve_line()?;
+                self.stage = SolverStage::Corner;
             }
+            SolverStage::Corner => {
+                self.solve_corner()?;
+                self.finish_line();
+            }
+            SolverStage::LastFour => {
+                self.solve_last_four_fields();
+                let moves = std::mem::take(&mut self.goal_return_moves);
+                self.apply_empty_field_moves_as_swaps(&moves);
+                self.stage = SolverStage::Done;
+            }
+            SolverStage::Done => return Ok(None),
+        }
 
-            // Solve last field in the row
-            let cur_pos = match phase {
-                SolverPhase::Row => Coords {
-                    row: working_row,
-                    col: self.width - 1,
-                },
-                SolverPhase::Column => Coords {
-                    row: self.height - 1,
-                    col: working_col,
-                },
-            };
+        Ok(Some(&self.swaps[num_previous_swaps..]))
+    }
+
+    /// Get the positions of the row or column which is solved next, or `None`
+    /// if the next stage is not the start of a row or column.
+    pub(crate) fn next_line(&self) -> Option<Vec<Coords<i32>>> {
+        if self.stage != SolverStage::Line {
+            return None;
+        }
+
+        let positions = match self.phase {
+            SolverPhase::Row => (self.working_col..self.width)
+                .map(|col| Coords {
+                    row: self.working_row,
+                    col,
+                })
+                .collect(),
+            SolverPhase::Column => (self.working_row..self.height)
+                .map(|row| Coords {
+                    row,
+                    col: self.working_col,
+                })
+                .collect(),
+        };
+        Some(positions)
+    }
+
+    /// Whether a position belongs to an already solved row or column.
+    pub(crate) fn is_solved_pos(&self, pos: Coords<i32>) -> bool {
+        pos.row < self.working_row || pos.col < self.working_col
+    }
 
-            // Only enter the deterministic routine if the field is not yet in place
+    pub(crate) fn fields(&self) -> &[T] {
+        &self.fields
+    }
+
+    pub(crate) fn goal_array(&self) -> &[T] {
+        &self.goal_array
+    }
+
+    pub(crate) fn swaps(&self) -> &[(usize, usize)] {
+        &self.swaps
+    }
+
+    pub(crate) fn num_fixed_fields(&self) -> usize {
+        self.fixed_fields.len()
+    }
+
+    /// Apply swaps found by another solver which solve the row or column of
+    /// [`DacPuzzleSolver::next_line`] and continue with the next one.
+    pub(crate) fn apply_line_swaps(&mut self, swaps: &[(usize, usize)]) {
+        let positions = self.next_line().expect("next stage starts a row or column");
+        self.apply_swaps(swaps);
+
+        // The last field is not fixed, just like after solving the line with
+        // the corner routine
+        self.fixed_fields
+            .extend(positions[..positions.len() - 1].iter().copied());
+        self.finish_line();
+    }
+
+    /// Apply swaps found by another solver without changing the stage.
+    ///
+    /// The swaps may not move fixed fields.
+    pub(crate) fn apply_swaps(&mut self, swaps: &[(usize, usize)]) {
+        for &(empty_idx_before, empty_idx_after) in swaps {
+            self.fields.swap(empty_idx_before, empty_idx_after);
+            self.swaps.push((empty_idx_before, empty_idx_after));
+            self.empty_field_pos = get_coords_from_idx(empty_idx_after as i32, self.width);
+        }
+    }
+
+    /// Advance to the next row or column after solving the current one.
+    fn finish_line(&mut self) {
+        match self.phase {
+            SolverPhase::Row => self.working_row += 1,
+            SolverPhase::Column => self.working_col += 1,
+        }
+        self.stage = self.next_line_stage();
+    }
+
+    /// Continue with the next row or column, or with the last fields if only a
+    /// square of 2x2 is left.
+    ///
+    /// We solve a row if at least as many rows as columns are left and a column
+    /// otherwise. On square boards, this alternates between rows and columns.
+    /// On rectangular boards, the longer side is shortened until the remaining
+    /// part is square, e.g. a strip of two rows (like a 2xN board) is solved
+    /// column by column, two fields at a time.
+    /// Solving a row requires at least three remaining rows and solving a
+    /// column at least three remaining columns, except for the 2x2 square.
+    fn next_line_stage(&mut self) -> SolverStage {
+        let remaining_width = self.width - self.working_col;
+        let remaining_height = self.height - self.working_row;
+        if remaining_width < 2 || remaining_height < 2 {
+            return SolverStage::LastFour;
+        }
+
+        self.phase = match remaining_height >= remaining_width {
+            true => SolverPhase::Row,
+            false => SolverPhase::Column,
+        };
+        SolverStage::Line
+    }
+
+    /// Solve the fields of the current row or column until the second last.
+    fn solve_line(&mut self) -> Result<(), LibError> {
+        let positions: Vec<_> = match self.phase {
+            // Solve fields in the row starting at `working_col`
+            SolverPhase::Row => (self.working_col..self.width - 1)
+                .map(|col| Coords {
+                    row: self.working_row,
+                    col,
+                })
+                .collect(),
+            // Solve fields in the column starting at `working_row`
+            SolverPhase::Column => (self.working_row..self.height - 1)
+                .map(|row| Coords {
+                    row,
+                    col: self.working_col,
+                })
+                .collect(),
+        };
+
+        for cur_pos in positions {
+            // Position that we want to fill with the right value/field
             let cur_pos_value = self.value_at_pos(cur_pos)?;
             let cur_pos_goal_value = self.goal_value_of_pos(cur_pos)?;
             if cur_pos_value != cur_pos_goal_value {
-                self.swap_corner_fields_to_goal(cur_pos_goal_value, cur_pos, phase)?;
-            }
-
-            // Prepare next iteration step
-            match phase {
-                SolverPhase::Row => {
-                    working_row += 1;
-                    phase = SolverPhase::Column;
-                }
-                SolverPhase::Column => {
-                    working_col += 1;
-                    phase = SolverPhase::Row
-                }
+                let goal_value_pos = self.pos_of_value(cur_pos_goal_value)?;
+                self.swap_field_to_goal_pos(goal_value_pos, cur_pos, self.phase);
             }
+            self.fixed_fields.insert(cur_pos);
         }
 
-        self.solve_last_four_fields();
+        Ok(())
+    }
 
-        Ok(self.swaps.clone())
+    /// Solve the last field in the current row or column.
+    fn solve_corner(&mut self) -> Result<(), LibError> {
+        let cur_pos = match self.phase {
+            SolverPhase::Row => Coords {
+                row: self.working_row,
+                col: self.width - 1,
+            },
+            SolverPhase::Column => Coords {
+                row: self.height - 1,
+                col: self.working_col,
+            },
+        };
+
+        // Only enter the deterministic routine if the field is not yet in place
+        let cur_pos_value = self.value_at_pos(cur_pos)?;
+        let cur_pos_goal_value = self.goal_value_of_pos(cur_pos)?;
+        if cur_pos_value != cur_pos_goal_value {
+            self.swap_corner_fields_to_goal(cur_pos_goal_value, cur_pos, self.phase)?;
+        }
+
+        Ok(())
     }
 
     /// Move a field to a goal position.
@@ -203,7 +445,7 @@ impl DacPuzzleSolver {
     /// Swap the corner fields at the end of a row/column into position.
     fn swap_corner_fields_to_goal(
         &mut self,
-        field_value: u8,
+        field_value: T,
         field_goal_pos: Coords<i32>,
         phase: SolverPhase,
     ) -> Result<(), LibError> {
@@ -254,7 +496,7 @@ impl DacPuzzleSolver {
         // In this case, our routine would fail to find a path because it cannot
         // move the targeted field (2) or any of the already sorted fields (0
         // and 1). Thus, we have to check for and handle this case explicitly.
-        let empty_field_val = (self.width * self.height - 1) as u8;
+        let empty_field_val = get_empty_field_id((self.width * self.height) as usize)?;
         if self.value_at_pos(field_goal_pos)? == empty_field_val
             && self.value_at_pos(empty_field_target_pos)? == field_value
         {
@@ -309,17 +551,14 @@ impl DacPuzzleSolver {
         let mut parent_field = HashMap::new();
 
         // Set of seen fields and queue of fields to explore for BFS algorithm.
-        let mut seen_neighbours: HashSet<Coords<i32>> = HashSet::new();
+        // Fields are marked as seen when they are queued, so that every field
+        // is queued at most once.
+        let mut seen_neighbours: HashSet<Coords<i32>> = HashSet::from([empty_field]);
         let mut to_discover = VecDeque::from([empty_field]);
 
         // Run BFS (excluding fixed fields) from empty field until we find
         // the target field.
         'expansion: while let Some(cur_field) = to_discover.pop_front() {
-            // Mark neighbour as seen/processed for BFS. We do this before
-            // looping through the neighbours so that we can break as soon as we
-            // see the target field.
-            seen_neighbours.insert(cur_field);
-
             // Identify neighbours
             let neighbours =
                 [(-1, 0), (1, 0), (0, 1), (0, -1)]
@@ -329,10 +568,9 @@ impl DacPuzzleSolver {
                             row: cur_field.row + d_row,
                             col: cur_field.col + d_col,
                         };
-                        // Filter out fields which are outside of the board, already
-                        // processed or in the fixed set.
+                        // Filter out fields which are outside of the board or in the
+                        // fixed set.
                         match in_bounds(neighbour.row, neighbour.col, self.width, self.height)
-                            && !seen_neighbours.contains(&neighbour)
                             && !self.fixed_fields.contains(&neighbour)
                             && neighbour != field
                         {
@@ -341,9 +579,12 @@ impl DacPuzzleSolver {
                         }
                     });
 
-            // Add the current field as parent for all neighbours and queue them
-            // to be processed.
+            // Add the current field as parent for all unseen neighbours and
+            // queue them to be processed.
             for neighbour in neighbours {
+                if !seen_neighbours.insert(neighbour) {
+                    continue;
+                }
                 parent_field.insert(neighbour, cur_field);
                 to_discover.push_back(neighbour);
                 // If our target field is among the neighbours, terminate the
@@ -425,9 +666,14 @@ impl DacPuzzleSolver {
     /// Apply a path of moves as swaps.
     ///
     /// This records the swaps in `self.swaps` and updates the `self.fields`
-    /// and the `self.empty_field_pos` accordingly.
+    /// and the `self.empty_field_pos` accordingly. Steps to the current
+    /// position of the empty field are skipped.
     fn apply_empty_field_moves_as_swaps(&mut self, moves: &[Coords<i32>]) {
         for step in moves {
+            if *step == self.empty_field_pos {
+                continue;
+            }
+
             let step_idx: i32 = get_idx_from_coords(*step, self.width);
             let empty_field_idx: i32 = get_idx_from_coords(self.empty_field_pos, self.width);
 
@@ -442,12 +688,12 @@ impl DacPuzzleSolver {
     }
 
     /// Get the position (`Coords<T>`) of a value
-    fn pos_of_value(&self, val: u8) -> Result<Coords<i32>, LibError> {
+    fn pos_of_value(&self, val: T) -> Result<Coords<i32>, LibError> {
         get_idx_of_val(&self.fields, val).map(|idx| get_coords_from_idx(idx as i32, self.width))
     }
 
     /// Get the value at a given position
-    fn value_at_pos(&self, pos: Coords<i32>) -> Result<u8, LibError> {
+    fn value_at_pos(&self, pos: Coords<i32>) -> Result<T, LibError> {
         let idx: i32 = get_idx_from_coords(pos, self.width);
         self.fields
             .get(idx as usize)
@@ -459,7 +705,7 @@ impl DacPuzzleSolver {
     }
 
     /// Get the goal value that a position should have in the solved puzzle
-    fn goal_value_of_pos(&self, pos: Coords<i32>) -> Result<u8, LibError> {
+    fn goal_value_of_pos(&self, pos: Coords<i32>) -> Result<T, LibError> {
         let idx: usize = get_idx_from_coords::<i32>(pos, self.width) as usize;
         self.goal_array
             .get(idx)
@@ -471,6 +717,36 @@ impl DacPuzzleSolver {
     }
 }
 
+/// Divide and conquer solver, see [`DacPuzzleSolver`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DivideAndConquer;
+
+impl Solver for DivideAndConquer {
+    fn info(&self) -> SolverInfo {
+        SolverInfo {
+            name: "divide-and-conquer",
+            optimal: false,
+            metric: MoveMetric::SingleTile,
+            square_only: false,
+            min_size: 2,
+            // Puzzles store `u8` field IDs, larger boards need a
+            // `DacPuzzleSolver<u16>`
+            max_num_fields: <u8 as TileId>::MAX_NUM_FIELDS,
+        }
+    }
+
+    fn solve_with_progress(
+        &self,
+        puzzle: &Puzzle,
+        observer: &mut dyn ProgressObserver,
+    ) -> Result<Solution, LibError> {
+        let mut solver =
+            DacPuzzleSolver::new(&puzzle.fields, puzzle.width as i32, puzzle.height as i32)?;
+        let swaps = solver.solve_puzzle_with_progress(observer)?;
+        Ok(Solution { swaps })
+    }
+}
+
 /// Identify the next step to move a field to on the way to the goal position.
 ///
 /// Depending on whether we solve a row or a column, we move the field first
@@ -636,55 +912,35 @@ fn get_fixed_corner_moves_horizontally(empty_pos: Coords<i32>) -> Vec<Coords<i32
 mod test {
 
     use super::*;
+    use crate::solver::verify::verify_solution;
 
     #[test]
     fn test_solving_regular_4_by_4() -> Result<(), LibError> {
-        let mut fields = vec![8, 5, 6, 1, 14, 4, 7, 2, 0, 13, 11, 9, 15, 12, 10, 3];
-        let target_fields = initialize_fields(fields.len());
+        let fields: Vec<u8> = vec![8, 5, 6, 1, 14, 4, 7, 2, 0, 13, 11, 9, 15, 12, 10, 3];
 
         let mut solver = DacPuzzleSolver::new(&fields, 4, 4)?;
         let swaps = solver.solve_puzzle()?;
 
-        for swap in swaps {
-            fields.swap(swap.0, swap.1);
-        }
-
-        assert_eq!(fields, target_fields);
-
-        Ok(())
+        verify_solution(&fields, 4, 4, &swaps)
     }
 
     #[test]
     fn test_corner_case_corner_presolved_row_end() -> Result<(), LibError> {
-        let mut fields = vec![2, 1, 5, 3, 0, 7, 8, 6, 4];
-        let target_fields = initialize_fields(fields.len());
+        let fields: Vec<u8> = vec![2, 1, 5, 3, 0, 7, 8, 6, 4];
 
         let mut solver = DacPuzzleSolver::new(&fields, 3, 3)?;
         let swaps = solver.solve_puzzle()?;
 
-        for swap in swaps {
-            fields.swap(swap.0, swap.1);
-        }
-
-        assert_eq!(fields, target_fields);
-
-        Ok(())
+        verify_solution(&fields, 3, 3, &swaps)
     }
 
     #[test]
     fn test_corner_case() -> Result<(), LibError> {
-        let mut fields = vec![2, 1, 5, 7, 3, 4, 0, 6, 8];
-        let target_fields = initialize_fields(fields.len());
+        let fields: Vec<u8> = vec![2, 1, 5, 7, 3, 4, 0, 6, 8];
 
         let mut solver = DacPuzzleSolver::new(&fields, 3, 3)?;
         let swaps = solver.solve_puzzle()?;
 
-        for swap in swaps {
-            fields.swap(swap.0, swap.1);
-        }
-
-        assert_eq!(fields, target_fields);
-
-        Ok(())
+        verify_solution(&fields, 3, 3, &swaps)
     }
 }
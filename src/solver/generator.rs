@@ -0,0 +1,329 @@
+//! Generator of uniformly random solvable puzzles
+//!
+//! A random walk of the empty field, like the shuffle of the web page, prefers
+//! states close to its start. Instead, we draw a uniformly random permutation
+//! of all fields. If it cannot be solved, we swap the first two fields which
+//! are not the empty field. This flips the parity and maps the unsolvable
+//! states one to one onto the solvable ones, so every solvable state is drawn
+//! with the same probability. On boards of a single row or column, the fields
+//! can never change their order, so the only solvable states are the sorted
+//! fields with the empty field at any position of the line.
+//!
+//! To animate a shuffle, [`get_swaps_between`] finds a swap sequence from any
+//! state to the generated one with the divide and conquer solver.
+//!
+//! Puzzles of a given difficulty have an exact optimal distance from the
+//! solved state, see [`random_fields_at_distance`].
+//!
+
+use std::ops::RangeInclusive;
+
+use rand::{seq::SliceRandom, Rng};
+use rustc_hash::FxHashSet;
+
+use crate::{
+    board::{
+        analyze_solvability, get_empty_field_id, get_swappable_neighbours, initialize_fields,
+        TileId,
+    },
+    error::LibError,
+    solver::{
+        divide_and_conquer::DacPuzzleSolver,
+        heuristic::{Heuristic, LinearConflict},
+        ida_star::find_swap_order_with_heuristic,
+        lookup_table::LookupTable,
+        peephole::cancel_inverse_pairs,
+    },
+};
+
+/// Difficulty of a puzzle by its optimal number of swaps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    /// Range of optimal distances of the difficulty.
+    ///
+    /// The ranges do not grow with the board size since proving the distance
+    /// takes exponentially longer, and the hardest 3x3 puzzles need 31 swaps.
+    pub fn distances(&self) -> RangeInclusive<usize> {
+        match self {
+            Difficulty::Easy => 6..=10,
+            Difficulty::Medium => 14..=20,
+            Difficulty::Hard => 24..=30,
+        }
+    }
+}
+
+/// Puzzle with a proven optimal distance from the solved state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DistancePuzzle {
+    pub fields: Vec<u8>,
+    /// Optimal number of swaps to solve the puzzle.
+    pub distance: usize,
+    /// Shortest swap sequence from the solved state to the puzzle.
+    pub swaps: Vec<(usize, usize)>,
+}
+
+/// Draw a uniformly random solvable puzzle state.
+pub fn random_solvable_fields<T: TileId, R: Rng + ?Sized>(
+    width: usize,
+    height: usize,
+    rng: &mut R,
+) -> Result<Vec<T>, LibError> {
+    let mut fields = initialize_fields(width * height)?;
+
+    if width == 1 || height == 1 {
+        if let Some(empty_field_val) = fields.pop() {
+            fields.insert(rng.gen_range(0..=fields.len()), empty_field_val);
+        }
+        return Ok(fields);
+    }
+
+    fields.shuffle(rng);
+
+    // Boards with less than two fields besides the empty field are always
+    // solvable
+    if !analyze_solvability(&fields, width, height)?.solvable {
+        let empty_field_val = get_empty_field_id(fields.len())?;
+        let tile_indices: Vec<usize> = (0..fields.len())
+            .filter(|&idx| fields[idx] != empty_field_val)
+            .take(2)
+            .collect();
+        fields.swap(tile_indices[0], tile_indices[1]);
+    }
+
+    Ok(fields)
+}
+
+/// Get swaps from the solved state to a puzzle state.
+pub fn get_swaps_from_goal<T: TileId>(
+    fields: &[T],
+    width: usize,
+    height: usize,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    get_swaps_between(&initialize_fields(fields.len())?, fields, width, height)
+}
+
+/// Get swaps from one puzzle state to another one.
+///
+/// The swaps are not the shortest ones but are found quickly on any board
+/// size.
+pub fn get_swaps_between<T: TileId>(
+    fields: &[T],
+    target: &[T],
+    width: usize,
+    height: usize,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    let mut solver = DacPuzzleSolver::with_goal(fields, target, width as i32, height as i32)?;
+    Ok(cancel_inverse_pairs(&solver.solve_puzzle()?))
+}
+
+/// Generate a random puzzle whose optimal distance from the solved state is in
+/// the given range.
+///
+/// We draw a distance from the range and walk from the solved state, always
+/// to a random neighbour one swap further away. The distances are proven with
+/// the embedded table on 3x3 boards and with IDA* on other boards, where
+/// `max_num_steps` limits the expanded states of every search. If the walk
+/// gets stuck in a state without a neighbour further away, it backtracks and
+/// remembers the state to not enter it again.
+///
+/// If no state has the drawn distance, no state is further away either, so we
+/// draw again from the smaller distances of the range. Returns
+/// [`LibError::NoPuzzleAtDistance`] only if the board has no state at any
+/// distance of the range.
+pub fn random_fields_at_distance<R: Rng + ?Sized>(
+    width: usize,
+    height: usize,
+    distances: RangeInclusive<usize>,
+    max_num_steps: usize,
+    rng: &mut R,
+) -> Result<DistancePuzzle, LibError> {
+    let certifier = DistanceCertifier::new(width, height, max_num_steps);
+
+    let mut remaining = distances.clone();
+    while !remaining.is_empty() {
+        let target_distance = rng.gen_range(remaining.clone());
+        if let Some(puzzle) = walk_to_distance(width, height, target_distance, &certifier, rng)? {
+            return Ok(puzzle);
+        }
+        let Some(max_distance) = target_distance.checked_sub(1) else {
+            break;
+        };
+        remaining = *remaining.start()..=max_distance;
+    }
+
+    Err(LibError::NoPuzzleAtDistance {
+        min: *distances.start(),
+        max: *distances.end(),
+    })
+}
+
+/// Walk from the solved state to a random state at the target distance,
+/// `None` if there is no such state.
+fn walk_to_distance<R: Rng + ?Sized>(
+    width: usize,
+    height: usize,
+    target_distance: usize,
+    certifier: &DistanceCertifier,
+    rng: &mut R,
+) -> Result<Option<DistancePuzzle>, LibError> {
+    // Every swap of the walk increases the distance by one, so the distance
+    // is the number of swaps so far
+    let mut fields = initialize_fields(width * height)?;
+    let mut swaps: Vec<(usize, usize)> = Vec::with_capacity(target_distance);
+    let mut stack = vec![WalkStep::new(fields.len() - 1, None, width, height, rng)];
+    let mut dead_ends: FxHashSet<Vec<u8>> = FxHashSet::default();
+
+    while let Some(step) = stack.last_mut() {
+        if swaps.len() == target_distance {
+            return Ok(Some(DistancePuzzle {
+                fields,
+                distance: target_distance,
+                swaps,
+            }));
+        }
+
+        let empty_field_idx = step.empty_field_idx;
+        match step.neighbours.pop() {
+            Some(neighbour_idx) => {
+                fields.swap(empty_field_idx, neighbour_idx);
+                if !dead_ends.contains(&fields) && certifier.is_further(&fields, swaps.len())? {
+                    swaps.push((empty_field_idx, neighbour_idx));
+                    stack.push(WalkStep::new(
+                        neighbour_idx,
+                        Some(empty_field_idx),
+                        width,
+                        height,
+                        rng,
+                    ));
+                } else {
+                    fields.swap(empty_field_idx, neighbour_idx);
+                }
+            }
+            None => {
+                // No walk from this state reaches the target distance
+                stack.pop();
+                if let Some((empty_idx_before, empty_idx_after)) = swaps.pop() {
+                    dead_ends.insert(fields.clone());
+                    fields.swap(empty_idx_before, empty_idx_after);
+                }
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// State of the walk with the neighbours which were not tried yet.
+struct WalkStep {
+    empty_field_idx: usize,
+    neighbours: Vec<usize>,
+}
+
+impl WalkStep {
+    /// Collect the neighbours in random order, without the previous position
+    /// of the empty field, which is one swap closer.
+    fn new<R: Rng + ?Sized>(
+        empty_field_idx: usize,
+        prev_empty_field_idx: Option<usize>,
+        width: usize,
+        height: usize,
+        rng: &mut R,
+    ) -> Self {
+        let mut neighbours: Vec<usize> = get_swappable_neighbours(width, height, empty_field_idx)
+            .filter(|&neighbour_idx| Some(neighbour_idx) != prev_empty_field_idx)
+            .collect();
+        neighbours.shuffle(rng);
+
+        Self {
+            empty_field_idx,
+            neighbours,
+        }
+    }
+}
+
+/// Proves the optimal distances of the states of a walk.
+struct DistanceCertifier {
+    width: usize,
+    height: usize,
+    max_num_steps: usize,
+    table: Option<&'static LookupTable>,
+    heuristic: LinearConflict,
+}
+
+impl DistanceCertifier {
+    fn new(width: usize, height: usize, max_num_steps: usize) -> Self {
+        let table = LookupTable::embedded_3x3();
+        Self {
+            width,
+            height,
+            max_num_steps,
+            table: ((width, height) == (table.width(), table.height())).then_some(table),
+            heuristic: LinearConflict::new(width, height),
+        }
+    }
+
+    /// Check whether a neighbour of a state at `distance` is one swap further
+    /// away from the solved state, otherwise it is one swap closer.
+    fn is_further(&self, fields: &[u8], distance: usize) -> Result<bool, LibError> {
+        if let Some(table) = self.table {
+            return Ok(table.distance(fields)? == distance + 1);
+        }
+
+        // The heuristic never overestimates and has the parity of the
+        // distance, so an estimate above the closer distance proves the
+        // further one without a search
+        if self.heuristic.estimate(fields) > distance {
+            return Ok(true);
+        }
+
+        let outcome = find_swap_order_with_heuristic(
+            fields,
+            self.width,
+            self.height,
+            self.max_num_steps,
+            &self.heuristic,
+        )?;
+        Ok(outcome.swaps.len() == distance + 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn test_swaps_from_goal() -> Result<(), LibError> {
+        let mut rng = StdRng::seed_from_u64(0);
+        for (width, height) in [(2, 2), (3, 3), (4, 4), (3, 5), (8, 8)] {
+            let fields: Vec<u8> = random_solvable_fields(width, height, &mut rng)?;
+            let swaps = get_swaps_from_goal(&fields, width, height)?;
+
+            let mut state: Vec<u8> = initialize_fields(width * height)?;
+            for (empty_idx_before, empty_idx_after) in swaps {
+                state.swap(empty_idx_before, empty_idx_after);
+            }
+            assert_eq!(state, fields);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_always_solvable() -> Result<(), LibError> {
+        let mut rng = StdRng::seed_from_u64(1);
+        for (width, height) in [(2, 2), (3, 3), (4, 4), (2, 5), (6, 3), (1, 4), (4, 1)] {
+            for _ in 0..100 {
+                let fields: Vec<u8> = random_solvable_fields(width, height, &mut rng)?;
+                assert!(analyze_solvability(&fields, width, height)?.solvable);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,412 @@
+//! Heuristics for informed puzzle solvers
+//!
+//! A heuristic estimates the number of swaps required to bring a state to the
+//! solved state. All heuristics in this module are admissible, i.e. they never
+//! overestimate the remaining number of swaps. Admissible heuristics can be
+//! combined with [`Max`] to get an estimate which is at least as good as each
+//! of its parts.
+//!
+//! Apart from the Manhattan distances, every heuristic adds a penalty for a
+//! specific situation on top of the Manhattan distance. The penalties of
+//! different heuristics may count the same swaps, so they must not be added
+//! up but only combined with [`Max`].
+//!
+//! [`MultiTileManhattan`] estimates the moves in the multi-tile metric (see
+//! [`crate::solver::move_metric`]) and is the only heuristic which is
+//! admissible for it.
+//!
+
+use crate::board::{get_idx_from_row_col, get_row_col_from_idx};
+
+/// Estimate of the number of swaps required to solve a puzzle.
+pub trait Heuristic {
+    /// Estimate the number of swaps to solve `fields`.
+    ///
+    /// The estimate must never exceed the actual number of swaps required.
+    fn estimate(&self, fields: &[u8]) -> usize;
+}
+
+impl<H: Heuristic + ?Sized> Heuristic for &H {
+    fn estimate(&self, fields: &[u8]) -> usize {
+        (**self).estimate(fields)
+    }
+}
+
+impl<H: Heuristic + ?Sized> Heuristic for Box<H> {
+    fn estimate(&self, fields: &[u8]) -> usize {
+        (**self).estimate(fields)
+    }
+}
+
+/// Maximum of two heuristics.
+///
+/// The maximum of two admissible heuristics is admissible as well.
+#[derive(Clone, Debug)]
+pub struct Max<A, B>(pub A, pub B);
+
+impl<A: Heuristic, B: Heuristic> Heuristic for Max<A, B> {
+    fn estimate(&self, fields: &[u8]) -> usize {
+        usize::max(self.0.estimate(fields), self.1.estimate(fields))
+    }
+}
+
+/// Sum of the Manhattan distances of all fields to their goal positions.
+///
+/// Every swap moves one field by one position, so each field needs at least
+/// as many swaps as its Manhattan distance.
+#[derive(Clone, Debug)]
+pub struct ManhattanDistance {
+    width: usize,
+}
+
+impl ManhattanDistance {
+    pub fn new(width: usize, _height: usize) -> Self {
+        Self { width }
+    }
+}
+
+impl Heuristic for ManhattanDistance {
+    fn estimate(&self, fields: &[u8]) -> usize {
+        manhattan_distance(fields, self.width)
+    }
+}
+
+/// Manhattan distance plus linear conflicts.
+///
+/// Two fields are in a linear conflict if both are in their goal row (or
+/// column) but in reversed order. One of them has to leave the row and come
+/// back, which takes two swaps not counted by the Manhattan distance. Per row
+/// and column, we count the minimum number of fields which have to leave so
+/// that the remaining ones are in order.
+#[derive(Clone, Debug)]
+pub struct LinearConflict {
+    width: usize,
+    height: usize,
+}
+
+impl LinearConflict {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height }
+    }
+}
+
+impl Heuristic for LinearConflict {
+    fn estimate(&self, fields: &[u8]) -> usize {
+        let empty_field_val = (fields.len() - 1) as u8;
+        let mut goal_positions = Vec::with_capacity(usize::max(self.width, self.height));
+        let mut num_conflicts = 0;
+
+        for row in 0..self.height {
+            goal_positions.clear();
+            for col in 0..self.width {
+                let value = fields[get_idx_from_row_col(row, col, self.width)];
+                let (goal_row, goal_col) = get_row_col_from_idx(value as usize, self.width);
+                if value != empty_field_val && goal_row == row {
+                    goal_positions.push(goal_col);
+                }
+            }
+            num_conflicts += goal_positions.len() - longest_increasing_subsequence(&goal_positions);
+        }
+
+        for col in 0..self.width {
+            goal_positions.clear();
+            for row in 0..self.height {
+                let value = fields[get_idx_from_row_col(row, col, self.width)];
+                let (goal_row, goal_col) = get_row_col_from_idx(value as usize, self.width);
+                if value != empty_field_val && goal_col == col {
+                    goal_positions.push(goal_row);
+                }
+            }
+            num_conflicts += goal_positions.len() - longest_increasing_subsequence(&goal_positions);
+        }
+
+        manhattan_distance(fields, self.width) + 2 * num_conflicts
+    }
+}
+
+/// Manhattan distance plus corner-tile penalties.
+///
+/// If a corner field is wrong but both fields next to the corner are in their
+/// goal positions, one of them has to move away to let the corner field pass
+/// and then come back. This takes two swaps not counted by the Manhattan
+/// distance. A neighbour field may be shared by two corners on small boards,
+/// so we only count corners whose neighbours were not counted before.
+#[derive(Clone, Debug)]
+pub struct CornerTiles {
+    width: usize,
+    /// Corner index with the indices of its horizontal and vertical neighbour.
+    corners: Vec<(usize, [usize; 2])>,
+}
+
+impl CornerTiles {
+    pub fn new(width: usize, height: usize) -> Self {
+        let mut corners = Vec::new();
+
+        if width >= 2 && height >= 2 {
+            let empty_field_idx = width * height - 1;
+            for (row, col) in [
+                (0, 0),
+                (0, width - 1),
+                (height - 1, 0),
+                (height - 1, width - 1),
+            ] {
+                let neighbour_row = if row == 0 { 1 } else { row - 1 };
+                let neighbour_col = if col == 0 { 1 } else { col - 1 };
+
+                let corner_idx = get_idx_from_row_col(row, col, width);
+                let neighbours = [
+                    get_idx_from_row_col(row, neighbour_col, width),
+                    get_idx_from_row_col(neighbour_row, col, width),
+                ];
+
+                // The goal of the empty field is no field that has to move
+                if corner_idx != empty_field_idx && !neighbours.contains(&empty_field_idx) {
+                    corners.push((corner_idx, neighbours));
+                }
+            }
+        }
+
+        Self { width, corners }
+    }
+}
+
+impl Heuristic for CornerTiles {
+    fn estimate(&self, fields: &[u8]) -> usize {
+        let mut counted: Vec<usize> = Vec::with_capacity(8);
+        let mut penalty = 0;
+
+        for (corner_idx, neighbours) in self.corners.iter() {
+            let corner_solved = fields[*corner_idx] as usize == *corner_idx;
+            let neighbours_solved = neighbours.iter().all(|&idx| fields[idx] as usize == idx);
+            let neighbours_counted = neighbours.iter().any(|idx| counted.contains(idx));
+
+            if !corner_solved && neighbours_solved && !neighbours_counted {
+                penalty += 2;
+                counted.extend(neighbours);
+            }
+        }
+
+        manhattan_distance(fields, self.width) + penalty
+    }
+}
+
+/// Manhattan distance plus last-moves penalty.
+///
+/// The last swap moves the empty field into the bottom right corner. The field
+/// moved by it is either the one which belongs left of the corner or the one
+/// which belongs above it, and it has to be in the corner before. If neither
+/// of them is in the last column or last row respectively, the one moved last
+/// takes a detour of two swaps not counted by the Manhattan distance.
+#[derive(Clone, Debug)]
+pub struct LastMoves {
+    width: usize,
+    height: usize,
+}
+
+impl LastMoves {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height }
+    }
+}
+
+impl Heuristic for LastMoves {
+    fn estimate(&self, fields: &[u8]) -> usize {
+        let manhattan = manhattan_distance(fields, self.width);
+        if manhattan == 0 || self.width < 2 || self.height < 2 {
+            return manhattan;
+        }
+
+        let left_value = get_idx_from_row_col(self.height - 1, self.width - 2, self.width);
+        let above_value = get_idx_from_row_col(self.height - 2, self.width - 1, self.width);
+
+        let mut left_col = 0;
+        let mut above_row = 0;
+        for (idx, &value) in fields.iter().enumerate() {
+            let (row, col) = get_row_col_from_idx(idx, self.width);
+            if value as usize == left_value {
+                left_col = col;
+            } else if value as usize == above_value {
+                above_row = row;
+            }
+        }
+
+        if left_col != self.width - 1 && above_row != self.height - 1 {
+            manhattan + 2
+        } else {
+            manhattan
+        }
+    }
+}
+
+/// Manhattan distance for the multi-tile metric.
+///
+/// A slide along a row moves at most `width - 1` fields by one column each and
+/// leaves the rows of all fields unchanged. So the horizontal part of the
+/// Manhattan distance shrinks by at most `width - 1` per horizontal slide, and
+/// the same holds for the vertical part and vertical slides. This is a lower
+/// bound of the moves in the multi-tile metric and therefore also of the swaps.
+#[derive(Clone, Debug)]
+pub struct MultiTileManhattan {
+    width: usize,
+    height: usize,
+}
+
+impl MultiTileManhattan {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height }
+    }
+}
+
+impl Heuristic for MultiTileManhattan {
+    fn estimate(&self, fields: &[u8]) -> usize {
+        let empty_field_val = fields.len() - 1;
+        let mut horizontal = 0;
+        let mut vertical = 0;
+
+        for (idx, &value) in fields.iter().enumerate() {
+            if value as usize == empty_field_val {
+                continue;
+            }
+            let (row, col) = get_row_col_from_idx(idx, self.width);
+            let (goal_row, goal_col) = get_row_col_from_idx(value as usize, self.width);
+            horizontal += col.abs_diff(goal_col);
+            vertical += row.abs_diff(goal_row);
+        }
+
+        horizontal.div_ceil(usize::max(self.width - 1, 1))
+            + vertical.div_ceil(usize::max(self.height - 1, 1))
+    }
+}
+
+/// Sum of the Manhattan distances of all fields except the empty field to
+/// their goal positions.
+pub(crate) fn manhattan_distance(fields: &[u8], width: usize) -> usize {
+    let empty_field_val = fields.len() - 1;
+
+    fields
+        .iter()
+        .enumerate()
+        .filter(|(_, &value)| value as usize != empty_field_val)
+        .map(|(idx, &value)| {
+            let (row, col): (usize, usize) = get_row_col_from_idx(idx, width);
+            let (goal_row, goal_col): (usize, usize) = get_row_col_from_idx(value as usize, width);
+            row.abs_diff(goal_row) + col.abs_diff(goal_col)
+        })
+        .sum()
+}
+
+/// Length of the longest strictly increasing subsequence.
+fn longest_increasing_subsequence(values: &[usize]) -> usize {
+    // Smallest tail value of all increasing subsequences of length `i + 1`
+    let mut tails: Vec<usize> = Vec::with_capacity(values.len());
+    for &value in values {
+        match tails.binary_search(&value) {
+            Ok(_) => (),
+            Err(pos) if pos == tails.len() => tails.push(value),
+            Err(pos) => tails[pos] = value,
+        }
+    }
+    tails.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        board::initialize_fields,
+        solver::{
+            move_metric::MoveMetric,
+            test::state_space::{all_distances, all_distances_with_metric},
+        },
+    };
+
+    fn assert_admissible(width: usize, height: usize) {
+        let manhattan = ManhattanDistance::new(width, height);
+        let linear_conflict = LinearConflict::new(width, height);
+        let corner_tiles = CornerTiles::new(width, height);
+        let last_moves = LastMoves::new(width, height);
+
+        for (fields, distance) in all_distances(width, height) {
+            let manhattan_estimate = manhattan.estimate(&fields);
+            for estimate in [
+                manhattan_estimate,
+                linear_conflict.estimate(&fields),
+                corner_tiles.estimate(&fields),
+                last_moves.estimate(&fields),
+            ] {
+                assert!(estimate <= distance, "{fields:?}: {estimate} > {distance}");
+                assert!(estimate >= manhattan_estimate);
+                assert_eq!(estimate % 2, distance % 2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_admissible_2x3() {
+        assert_admissible(2, 3);
+        assert_admissible(3, 2);
+    }
+
+    #[test]
+    fn test_admissible_3x3() {
+        assert_admissible(3, 3);
+    }
+
+    #[test]
+    fn test_multi_tile_manhattan_admissible() {
+        for (width, height) in [(2, 3), (3, 2), (3, 3)] {
+            let heuristic = MultiTileManhattan::new(width, height);
+            for (fields, distance) in
+                all_distances_with_metric(width, height, MoveMetric::MultiTile)
+            {
+                let estimate = heuristic.estimate(&fields);
+                assert!(estimate <= distance, "{fields:?}: {estimate} > {distance}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_on_goal() {
+        let fields = initialize_fields(16).unwrap();
+        assert_eq!(ManhattanDistance::new(4, 4).estimate(&fields), 0);
+        assert_eq!(LinearConflict::new(4, 4).estimate(&fields), 0);
+        assert_eq!(CornerTiles::new(4, 4).estimate(&fields), 0);
+        assert_eq!(LastMoves::new(4, 4).estimate(&fields), 0);
+        assert_eq!(MultiTileManhattan::new(4, 4).estimate(&fields), 0);
+    }
+
+    #[test]
+    fn test_linear_conflict() {
+        // 1 and 0 are swapped in their goal row
+        let fields = [1, 0, 2, 3, 4, 5, 6, 7, 8];
+        assert_eq!(ManhattanDistance::new(3, 3).estimate(&fields), 2);
+        assert_eq!(LinearConflict::new(3, 3).estimate(&fields), 4);
+
+        // 2 1 0 in the first row needs two fields to leave
+        let fields = [2, 1, 0, 3, 4, 5, 6, 7, 8];
+        assert_eq!(LinearConflict::new(3, 3).estimate(&fields), 4 + 4);
+    }
+
+    #[test]
+    fn test_corner_tiles() {
+        // The top left corner is wrong while 1 and 4 are in place
+        let fields = [5, 1, 2, 3, 4, 0, 6, 7, 8];
+        assert_eq!(ManhattanDistance::new(3, 3).estimate(&fields), 6);
+        assert_eq!(CornerTiles::new(3, 3).estimate(&fields), 8);
+    }
+
+    #[test]
+    fn test_max() {
+        let fields = [1, 0, 2, 3, 4, 5, 6, 7, 8];
+        let heuristic = Max(ManhattanDistance::new(3, 3), LinearConflict::new(3, 3));
+        assert_eq!(heuristic.estimate(&fields), 4);
+    }
+
+    #[test]
+    fn test_longest_increasing_subsequence() {
+        assert_eq!(longest_increasing_subsequence(&[]), 0);
+        assert_eq!(longest_increasing_subsequence(&[0, 1, 2]), 3);
+        assert_eq!(longest_increasing_subsequence(&[2, 1, 0]), 1);
+        assert_eq!(longest_increasing_subsequence(&[1, 3, 0, 2, 4]), 3);
+    }
+}
@@ -0,0 +1,177 @@
+//! Hints for the next move of a player
+//!
+//! A hint is the first swap of a solution together with the number of swaps
+//! which remain to solve the puzzle. It is computed with the best method
+//! which is fast enough to run after every move:
+//!
+//! - 3x3 boards look up optimal moves in the embedded table (see
+//!   [`LookupTable`]).
+//! - Boards with up to 16 fields run IDA* with a limited number of expanded
+//!   states. With the LinearConflict heuristic, this finds optimal moves for
+//!   3x4 states but only for shallow 4x4 states, since uniformly random 4x4
+//!   states are around 50 swaps away. A loaded pattern database makes deeper
+//!   4x4 states exact, about a quarter of the random ones with a 6-6-3
+//!   partition. Without a database, states whose estimate is too large for
+//!   the limit skip the search.
+//! - All other boards, and states where IDA* is skipped or exceeds its limit,
+//!   take the first swap of the divide and conquer solution, after removing
+//!   swaps which undo each other. The distance is then the length of this
+//!   solution and an upper bound of the optimal one.
+//!
+
+use crate::{
+    error::LibError,
+    solver::{
+        divide_and_conquer::DacPuzzleSolver,
+        heuristic::{Heuristic, LinearConflict},
+        ida_star::find_swap_order_with_heuristic,
+        lookup_table::LookupTable,
+        pattern_database::{select_heuristic, PatternDatabase, SelectedHeuristic},
+        peephole::cancel_inverse_pairs,
+    },
+};
+
+/// Largest number of fields for which IDA* searches an optimal move.
+const MAX_NUM_FIELDS_SEARCH: usize = 16;
+
+/// Limit of expanded states of IDA*, which keeps a hint within a few tens of
+/// milliseconds.
+const MAX_NUM_STEPS_SEARCH: usize = 200_000;
+
+/// Largest LinearConflict estimate for which IDA* without a pattern database
+/// is tried. Above it, the search almost never ends within its limit of
+/// expanded states, but takes over 100 ms to find out.
+const MAX_ESTIMATE_SEARCH: usize = 34;
+
+/// Best next move and the remaining distance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hint {
+    /// Next swap as the empty field index before and after it, `None` if the
+    /// puzzle is solved.
+    pub swap: Option<(usize, usize)>,
+    /// Number of swaps to solve the puzzle, including the next one.
+    pub distance: usize,
+    /// Whether the distance is the optimal one, otherwise it is the length of
+    /// a solution starting with the swap.
+    pub exact: bool,
+}
+
+/// Get a hint for the next swap to solve a puzzle.
+///
+/// The pattern database guides the optimal search, see [`select_heuristic`].
+pub fn best_next_move(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    pattern_database: Option<&PatternDatabase>,
+) -> Result<Hint, LibError> {
+    let table = LookupTable::embedded_3x3();
+    if (width, height) == (table.width(), table.height()) {
+        let swaps = table.find_swap_order(fields)?;
+        return Ok(hint_from_swaps(&swaps, true));
+    }
+
+    if width * height <= MAX_NUM_FIELDS_SEARCH {
+        match find_optimal_swaps(fields, width, height, pattern_database)? {
+            Some(swaps) => return Ok(hint_from_swaps(&swaps, true)),
+            None => log::debug!("Falling back to divide and conquer for the hint"),
+        }
+    }
+
+    let mut solver = DacPuzzleSolver::new(fields, width as i32, height as i32)?;
+    let swaps = cancel_inverse_pairs(&solver.solve_puzzle()?);
+    Ok(hint_from_swaps(&swaps, false))
+}
+
+/// Search an optimal solution with IDA*, `None` if the search is skipped or
+/// exceeds its limit.
+fn find_optimal_swaps(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    pattern_database: Option<&PatternDatabase>,
+) -> Result<Option<Vec<(usize, usize)>>, LibError> {
+    let heuristic = select_heuristic(
+        pattern_database,
+        width,
+        height,
+        LinearConflict::new(width, height),
+    );
+    if let SelectedHeuristic::Fallback(fallback) = &heuristic {
+        if fallback.estimate(fields) > MAX_ESTIMATE_SEARCH {
+            return Ok(None);
+        }
+    }
+
+    let outcome =
+        find_swap_order_with_heuristic(fields, width, height, MAX_NUM_STEPS_SEARCH, &heuristic);
+
+    match outcome {
+        Ok(outcome) => Ok(Some(outcome.swaps)),
+        Err(LibError::MaxNumStepsReached(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn hint_from_swaps(swaps: &[(usize, usize)], exact: bool) -> Hint {
+    Hint {
+        swap: swaps.first().copied(),
+        distance: swaps.len(),
+        exact,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_solved() -> Result<(), LibError> {
+        for (width, height) in [(3, 3), (4, 4), (6, 6)] {
+            let fields: Vec<u8> = (0..(width * height) as u8).collect();
+            let hint = best_next_move(&fields, width, height, None)?;
+            assert_eq!(hint.swap, None);
+            assert_eq!(hint.distance, 0);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_optimal_4x4() -> Result<(), LibError> {
+        let fields = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 15, 14];
+        let hint = best_next_move(&fields, 4, 4, None)?;
+        assert_eq!(
+            hint,
+            Hint {
+                swap: Some((14, 15)),
+                distance: 1,
+                exact: true,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_skip_deep_search() -> Result<(), LibError> {
+        // Nearly reversed fields, far beyond the limit of expanded states
+        let mut fields: Vec<u8> = (0..15).rev().collect();
+        fields.swap(0, 1);
+        fields.push(15);
+        assert!(LinearConflict::new(4, 4).estimate(&fields) > MAX_ESTIMATE_SEARCH);
+        assert!(find_optimal_swaps(&fields, 4, 4, None)?.is_none());
+
+        let hint = best_next_move(&fields, 4, 4, None)?;
+        assert!(!hint.exact);
+        assert!(hint.swap.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        let fields = [1, 0, 2, 3, 4, 5, 6, 7, 8];
+        assert!(matches!(
+            best_next_move(&fields, 3, 3, None),
+            Err(LibError::Unsolvable)
+        ));
+    }
+}
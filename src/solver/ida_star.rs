@@ -0,0 +1,345 @@
+//! Optimal puzzle solver based on iterative deepening A* (IDA*)
+//!
+//! This runs depth-first searches with a growing bound on the estimated total
+//! path length `g + h`, where `g` is the number of swaps so far and `h` is an
+//! estimate of the remaining swaps by a [`Heuristic`]. Since the heuristics
+//! never overestimate the number of remaining swaps, the first solution found
+//! is a shortest one. Only the current path is kept in
+//! memory, so the memory usage grows linearly with the solution depth.
+//!
+
+use std::sync::Arc;
+
+use crate::{
+    board::{ensure_solvable, get_empty_field_idx, initialize_fields},
+    error::LibError,
+    solver::{
+        heuristic::{Heuristic, ManhattanDistance, MultiTileManhattan},
+        move_metric::{apply_slide, get_move_targets, MoveMetric, Slide},
+        pattern_database::{select_heuristic, PatternDatabase},
+        progress::{NoProgress, ProgressObserver, ProgressReporter, REPORT_INTERVAL},
+        registry::{Puzzle, Solution, Solver, SolverInfo},
+    },
+};
+
+/// Find the swap order to solve a puzzle
+///
+/// The returned swaps have the same format as the ones returned by
+/// [`crate::solver::optimal::find_swap_order`]. `max_num_steps` limits the
+/// number of expanded states over all iterations. The search is guided by the
+/// Manhattan distance.
+pub fn find_swap_order(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    max_num_steps: usize,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    let heuristic = ManhattanDistance::new(width, height);
+    find_swap_order_with_heuristic(fields, width, height, max_num_steps, &heuristic)
+        .map(|outcome| outcome.swaps)
+}
+
+/// Find the swap order to solve a puzzle guided by a given heuristic.
+///
+/// Besides the swaps, this returns the number of expanded states which allows
+/// to compare different heuristics.
+pub fn find_swap_order_with_heuristic<H: Heuristic>(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    max_num_steps: usize,
+    heuristic: &H,
+) -> Result<SearchOutcome, LibError> {
+    find_swap_order_with_progress(
+        fields,
+        width,
+        height,
+        max_num_steps,
+        heuristic,
+        &mut NoProgress,
+    )
+}
+
+/// Find the swap order guided by a given heuristic and report the progress.
+///
+/// The observer is called every few thousand expanded states with the bound of
+/// the current iteration and may cancel the search.
+pub fn find_swap_order_with_progress<H: Heuristic>(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    max_num_steps: usize,
+    heuristic: &H,
+    observer: &mut dyn ProgressObserver,
+) -> Result<SearchOutcome, LibError> {
+    find_swap_order_with_metric(
+        fields,
+        width,
+        height,
+        MoveMetric::SingleTile,
+        max_num_steps,
+        heuristic,
+        observer,
+    )
+}
+
+/// Find the swap order with the fewest moves in a metric and report the
+/// progress.
+///
+/// The heuristic must be admissible for the metric, e.g.
+/// [`crate::solver::heuristic::MultiTileManhattan`] for the multi-tile metric.
+/// The slides of a multi-tile solution are returned as their single swaps.
+pub fn find_swap_order_with_metric<H: Heuristic>(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    metric: MoveMetric,
+    max_num_steps: usize,
+    heuristic: &H,
+    observer: &mut dyn ProgressObserver,
+) -> Result<SearchOutcome, LibError> {
+    ensure_solvable(fields, width, height)?;
+    let empty_field_idx = get_empty_field_idx(fields)?;
+
+    let mut search = Search {
+        fields: fields.to_owned(),
+        goal: initialize_fields(fields.len())?,
+        width,
+        height,
+        metric,
+        heuristic,
+        swaps: Vec::new(),
+        num_moves: 0,
+        num_iterations: 0,
+        max_num_steps,
+        reporter: ProgressReporter::new(observer, REPORT_INTERVAL),
+    };
+
+    // The bound of the first iteration is the heuristic estimate of the
+    // initial state. Every following iteration uses the smallest estimate
+    // which exceeded the bound of the previous iteration.
+    let mut bound = heuristic.estimate(&search.fields);
+    loop {
+        log::debug!("Searching with bound {}", bound);
+        match search.search(empty_field_idx, None, bound)? {
+            Bounded::Found => break,
+            Bounded::Exceeded(next_bound) => bound = next_bound,
+        }
+    }
+
+    log::debug!("Number of iterations in solver: {}", search.num_iterations);
+    log::debug!("Number of moves to solve: {}", search.num_moves);
+
+    Ok(SearchOutcome {
+        swaps: search.swaps,
+        num_expanded: search.num_iterations,
+    })
+}
+
+/// Solution of a search together with search statistics.
+#[derive(Clone, Debug)]
+pub struct SearchOutcome {
+    /// Swaps solving the puzzle.
+    pub swaps: Vec<(usize, usize)>,
+    /// Number of expanded states over all iterations.
+    pub num_expanded: usize,
+}
+
+/// IDA* solver, see [`find_swap_order_with_metric`].
+///
+/// The search is guided by the pattern database, see [`select_heuristic`], or
+/// by the Manhattan distance. In the multi-tile metric, it is guided by
+/// [`MultiTileManhattan`] since the other heuristics count swaps.
+#[derive(Clone, Debug)]
+pub struct IdaStar {
+    pub max_num_steps: usize,
+    pub pattern_database: Option<Arc<PatternDatabase>>,
+    pub move_metric: MoveMetric,
+}
+
+impl Solver for IdaStar {
+    fn info(&self) -> SolverInfo {
+        SolverInfo {
+            name: "ida-star",
+            optimal: true,
+            metric: self.move_metric,
+            square_only: false,
+            min_size: 2,
+            max_num_fields: 25,
+        }
+    }
+
+    fn solve_with_progress(
+        &self,
+        puzzle: &Puzzle,
+        observer: &mut dyn ProgressObserver,
+    ) -> Result<Solution, LibError> {
+        let Puzzle {
+            fields,
+            width,
+            height,
+        } = puzzle;
+
+        let outcome = match self.move_metric {
+            MoveMetric::MultiTile => find_swap_order_with_metric(
+                fields,
+                *width,
+                *height,
+                MoveMetric::MultiTile,
+                self.max_num_steps,
+                &MultiTileManhattan::new(*width, *height),
+                observer,
+            ),
+            MoveMetric::SingleTile => find_swap_order_with_progress(
+                fields,
+                *width,
+                *height,
+                self.max_num_steps,
+                &select_heuristic(
+                    self.pattern_database.as_deref(),
+                    *width,
+                    *height,
+                    ManhattanDistance::new(*width, *height),
+                ),
+                observer,
+            ),
+        }?;
+
+        Ok(Solution {
+            swaps: outcome.swaps,
+        })
+    }
+}
+
+/// Result of a bounded depth-first search.
+enum Bounded {
+    /// The goal state was reached, the path is stored in `Search::swaps`.
+    Found,
+    /// The goal was not reached, holds the smallest exceeding estimate.
+    Exceeded(usize),
+}
+
+struct Search<'a, H> {
+    fields: Vec<u8>,
+    goal: Vec<u8>,
+    width: usize,
+    height: usize,
+    metric: MoveMetric,
+    heuristic: &'a H,
+    swaps: Vec<(usize, usize)>,
+    /// Number of moves on the current path.
+    num_moves: usize,
+    num_iterations: usize,
+    max_num_steps: usize,
+    reporter: ProgressReporter<'a>,
+}
+
+impl<H: Heuristic> Search<'_, H> {
+    /// Run a depth-first search below the current state limited by `bound`.
+    ///
+    /// Moves along the same row or column as the previous move are skipped.
+    /// In the single-tile metric, this only excludes undoing the last swap. In
+    /// the multi-tile metric, it also excludes slides which could be merged
+    /// with the previous one.
+    fn search(
+        &mut self,
+        empty_field_idx: usize,
+        prev_move: Option<Slide>,
+        bound: usize,
+    ) -> Result<Bounded, LibError> {
+        self.num_iterations += 1;
+        if self.num_iterations > self.max_num_steps {
+            return Err(LibError::MaxNumStepsReached(self.max_num_steps));
+        }
+        self.reporter.update(self.num_iterations, bound)?;
+
+        let estimate = self.num_moves + self.heuristic.estimate(&self.fields);
+        if estimate > bound {
+            return Ok(Bounded::Exceeded(estimate));
+        }
+
+        if self.fields == self.goal {
+            return Ok(Bounded::Found);
+        }
+
+        let mut next_bound = usize::MAX;
+        for target_idx in get_move_targets(self.width, self.height, empty_field_idx, self.metric) {
+            let slide = Slide {
+                from: empty_field_idx,
+                to: target_idx,
+            };
+            if prev_move.is_some_and(|prev_move| self.same_line(prev_move, slide)) {
+                continue;
+            }
+
+            // Apply the move, search below and undo it if the goal was not
+            // found on this path.
+            let num_swaps = self.swaps.len();
+            apply_slide(&mut self.fields, slide, self.width);
+            self.swaps.extend(slide.swaps(self.width));
+            self.num_moves += 1;
+
+            match self.search(target_idx, Some(slide), bound)? {
+                Bounded::Found => return Ok(Bounded::Found),
+                Bounded::Exceeded(estimate) => next_bound = next_bound.min(estimate),
+            }
+
+            self.num_moves -= 1;
+            self.swaps.truncate(num_swaps);
+            apply_slide(&mut self.fields, slide.reversed(), self.width);
+        }
+
+        Ok(Bounded::Exceeded(next_bound))
+    }
+
+    /// Check whether a move continues along the row or column of the previous
+    /// one.
+    fn same_line(&self, prev_move: Slide, slide: Slide) -> bool {
+        match self.metric {
+            MoveMetric::SingleTile => slide.to == prev_move.from,
+            MoveMetric::MultiTile => {
+                let is_horizontal = |slide: Slide| slide.from / self.width == slide.to / self.width;
+                is_horizontal(prev_move) == is_horizontal(slide)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_find_swap_order_zero_moves() -> Result<(), LibError> {
+        let fields = vec![0, 1, 2, 3];
+        let swap_order = find_swap_order(&fields, 2, 2, 10)?;
+        assert_eq!(swap_order, Vec::with_capacity(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_swap_order_one_move() -> Result<(), LibError> {
+        let fields = vec![0, 1, 3, 2];
+        let swap_order = find_swap_order(&fields, 2, 2, 10)?;
+        assert_eq!(swap_order, vec![(2, 3)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_swap_order_four_swaps() -> Result<(), LibError> {
+        let fields = vec![8, 1, 2, 0, 3, 5, 6, 4, 7];
+        let swap_order = find_swap_order(&fields, 3, 3, 256)?;
+        assert_eq!(swap_order, vec![(0, 3), (3, 4), (4, 7), (7, 8)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_num_steps_reached() {
+        let fields = vec![8, 1, 2, 0, 3, 5, 6, 4, 7];
+        assert!(matches!(
+            find_swap_order(&fields, 3, 3, 2),
+            Err(LibError::MaxNumStepsReached(2))
+        ));
+    }
+}
@@ -0,0 +1,383 @@
+//! Perfect lookup table of the distances of all states of small boards
+//!
+//! Small boards have few enough states to store the distance to the goal for
+//! every one of them. The table is built by a retrograde breadth-first-search
+//! from the goal state over the whole state space. A solver then walks the
+//! table downhill: every state has a neighbour which is one swap closer to the
+//! goal, so optimal solutions are found without any search.
+//!
+//! The entries are indexed by the position of the empty field and the rank of
+//! the order of the other fields. The rank is the Lehmer code of the order,
+//! i.e. for every field the number of smaller fields after it, read as a
+//! number in the factorial number system. Swapping the last two fields changes
+//! the parity of the order and the rank by one. Since only orders of one
+//! parity can be reached for every position of the empty field, both ranks
+//! share an entry and the table of a 3x3 board has `9 * 8! / 2 = 181,440`
+//! entries.
+//!
+//! The table of the 3x3 board is embedded into the binary. Tables are stored
+//! in a versioned and checksummed binary format:
+//!
+//! ```conf
+//! magic        4 bytes  "SPLT"
+//! version      u16
+//! width        u8
+//! height       u8
+//! num entries  u32
+//! distances    [u8; num entries]
+//! checksum     u64 (FNV-1a of all preceding bytes)
+//! ```
+//!
+//! All integers are stored in little endian byte order.
+//!
+
+use std::sync::OnceLock;
+
+use crate::{
+    board::{ensure_solvable, get_empty_field_idx, get_swappable_neighbours, initialize_fields},
+    error::LibError,
+    solver::{
+        move_metric::MoveMetric,
+        pattern_database::checksum,
+        progress::ProgressObserver,
+        registry::{Puzzle, Solution, Solver, SolverInfo},
+    },
+};
+
+const MAGIC: &[u8; 4] = b"SPLT";
+const FORMAT_VERSION: u16 = 1;
+const HEADER_LEN: usize = 12;
+const CHECKSUM_LEN: usize = 8;
+const UNSEEN: u8 = u8::MAX;
+
+/// Largest number of fields of a board with a lookup table, larger tables take
+/// too much memory.
+pub const MAX_NUM_FIELDS: usize = 9;
+
+static EMBEDDED_TABLE_3X3: OnceLock<LookupTable> = OnceLock::new();
+
+/// Distances of all states of a board to the goal state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LookupTable {
+    width: usize,
+    height: usize,
+    distances: Vec<u8>,
+}
+
+impl LookupTable {
+    /// Build the table by a breadth-first-search from the goal state.
+    pub fn build(width: usize, height: usize) -> Result<Self, LibError> {
+        validate_board_size(width, height)?;
+
+        let goal = initialize_fields(width * height)?;
+        let mut distances = vec![UNSEEN; num_entries(width, height)];
+        distances[index(&goal)] = 0;
+
+        let mut level = vec![goal];
+        let mut distance = 0;
+        while !level.is_empty() {
+            distance += 1;
+
+            let mut next_level = Vec::new();
+            for fields in level {
+                let empty_field_idx = get_empty_field_idx(&fields)?;
+                for neighbour_idx in get_swappable_neighbours(width, height, empty_field_idx) {
+                    let mut next_fields = fields.clone();
+                    next_fields.swap(empty_field_idx, neighbour_idx);
+
+                    let entry = &mut distances[index(&next_fields)];
+                    if *entry == UNSEEN {
+                        *entry = distance;
+                        next_level.push(next_fields);
+                    }
+                }
+            }
+            level = next_level;
+        }
+
+        log::debug!(
+            "Built lookup table for {}x{} boards with maximum distance {}",
+            width,
+            height,
+            distance - 1
+        );
+
+        Ok(Self {
+            width,
+            height,
+            distances,
+        })
+    }
+
+    /// Get the table of the 3x3 board embedded into the binary.
+    pub fn embedded_3x3() -> &'static Self {
+        EMBEDDED_TABLE_3X3.get_or_init(|| {
+            Self::from_bytes(include_bytes!("../../assets/lookup_table_3x3.bin"))
+                .expect("embedded lookup table is valid")
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Look up the number of swaps of a shortest solution.
+    pub fn distance(&self, fields: &[u8]) -> Result<usize, LibError> {
+        if fields.len() != self.width * self.height {
+            return Err(LibError::FieldsBoardMismatch {
+                len: fields.len(),
+                expected: (self.width * self.height) as i32,
+            });
+        }
+
+        // Unsolvable states share their entry with a solvable one
+        ensure_solvable(fields, self.width, self.height)?;
+
+        Ok(self.distances[index(fields)] as usize)
+    }
+
+    /// Find a shortest swap order by moving the empty field to a neighbour
+    /// one swap closer to the goal until the goal is reached.
+    pub fn find_swap_order(&self, fields: &[u8]) -> Result<Vec<(usize, usize)>, LibError> {
+        let mut distance = self.distance(fields)?;
+        let mut fields = fields.to_owned();
+        let mut empty_field_idx = get_empty_field_idx(&fields)?;
+
+        let mut swaps = Vec::with_capacity(distance);
+        while distance > 0 {
+            let neighbour_idx = get_swappable_neighbours(self.width, self.height, empty_field_idx)
+                .find(|&neighbour_idx| {
+                    fields.swap(empty_field_idx, neighbour_idx);
+                    let is_closer = self.distances[index(&fields)] as usize == distance - 1;
+                    fields.swap(empty_field_idx, neighbour_idx);
+                    is_closer
+                })
+                .ok_or(LibError::TerminatedWithoutSolution)?;
+
+            fields.swap(empty_field_idx, neighbour_idx);
+            swaps.push((empty_field_idx, neighbour_idx));
+            empty_field_idx = neighbour_idx;
+            distance -= 1;
+        }
+
+        Ok(swaps)
+    }
+
+    /// Serialize the table to the binary format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.distances.len() + CHECKSUM_LEN);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.push(self.width as u8);
+        bytes.push(self.height as u8);
+        bytes.extend_from_slice(&(self.distances.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.distances);
+
+        let checksum = checksum(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+
+        bytes
+    }
+
+    /// Deserialize a table from the binary format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LibError> {
+        if bytes.len() < HEADER_LEN + CHECKSUM_LEN {
+            return Err(LibError::InvalidLookupTable("too short"));
+        }
+
+        let (payload, checksum_bytes) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+        let (header, distances) = payload.split_at(HEADER_LEN);
+
+        if &header[..4] != MAGIC {
+            return Err(LibError::InvalidLookupTable("missing magic bytes"));
+        }
+
+        let version = u16::from_le_bytes([header[4], header[5]]);
+        if version != FORMAT_VERSION {
+            return Err(LibError::UnsupportedLookupTableVersion(version));
+        }
+
+        let expected_checksum = u64::from_le_bytes(checksum_bytes.try_into().unwrap());
+        let actual_checksum = checksum(payload);
+        if expected_checksum != actual_checksum {
+            return Err(LibError::ChecksumMismatch {
+                expected: expected_checksum,
+                actual: actual_checksum,
+            });
+        }
+
+        let (width, height) = (header[6] as usize, header[7] as usize);
+        validate_board_size(width, height)?;
+
+        let num_entries = u32::from_le_bytes(header[8..12].try_into().unwrap()) as usize;
+        if num_entries != self::num_entries(width, height) || distances.len() != num_entries {
+            return Err(LibError::InvalidLookupTable("unexpected number of entries"));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            distances: distances.to_vec(),
+        })
+    }
+}
+
+/// Solver looking up optimal solutions of 3x3 boards in the embedded table,
+/// see [`LookupTable::find_swap_order`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LookupTableSolver;
+
+impl Solver for LookupTableSolver {
+    fn info(&self) -> SolverInfo {
+        SolverInfo {
+            name: "lookup-table",
+            optimal: true,
+            metric: MoveMetric::SingleTile,
+            square_only: true,
+            min_size: 3,
+            max_num_fields: 9,
+        }
+    }
+
+    fn solve_with_progress(
+        &self,
+        puzzle: &Puzzle,
+        _observer: &mut dyn ProgressObserver,
+    ) -> Result<Solution, LibError> {
+        let table = LookupTable::embedded_3x3();
+        if (puzzle.width, puzzle.height) != (table.width(), table.height()) {
+            return Err(LibError::UnsupportedSize {
+                width: puzzle.width,
+                height: puzzle.height,
+            });
+        }
+
+        let swaps = table.find_swap_order(&puzzle.fields)?;
+        Ok(Solution { swaps })
+    }
+}
+
+/// Check that the table of a board fits into memory.
+fn validate_board_size(width: usize, height: usize) -> Result<(), LibError> {
+    if width < 2 || height < 2 || width * height > MAX_NUM_FIELDS {
+        return Err(LibError::UnsupportedSize { width, height });
+    }
+    Ok(())
+}
+
+/// Number of entries, i.e. half of all orders of the fields for every
+/// position of the empty field.
+fn num_entries(width: usize, height: usize) -> usize {
+    let num_fields = width * height;
+    num_fields * (1..num_fields).product::<usize>() / 2
+}
+
+/// Index of the entry of a state.
+fn index(fields: &[u8]) -> usize {
+    let empty_field_val = (fields.len() - 1) as u8;
+    let mut empty_field_idx = 0;
+    let mut rank = 0;
+    let mut num_others = 0;
+    for (idx, &value) in fields.iter().enumerate() {
+        if value == empty_field_val {
+            empty_field_idx = idx;
+            continue;
+        }
+
+        // Digit of the Lehmer code, the number of smaller fields after this
+        // one, with the base of the number of remaining fields
+        let num_smaller_after = fields[idx + 1..]
+            .iter()
+            .filter(|&&other| other < value)
+            .count();
+        rank = rank * (fields.len() - 1 - num_others) + num_smaller_after;
+        num_others += 1;
+    }
+
+    let num_orders_per_position: usize = (1..fields.len()).product();
+    empty_field_idx * num_orders_per_position / 2 + rank / 2
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_index_pairs_swapped_last_fields() {
+        let fields = [3, 1, 2, 0];
+        assert_eq!(index(&fields), index(&[3, 1, 0, 2]));
+        assert_ne!(index(&fields), index(&[3, 2, 1, 0]));
+        assert_eq!(index(&[0, 1, 2, 3]), 3 * 3);
+    }
+
+    #[test]
+    fn test_build_2x2() -> Result<(), LibError> {
+        let table = LookupTable::build(2, 2)?;
+        assert_eq!(table.distances.len(), 12);
+        assert!(table.distances.iter().all(|&distance| distance <= 6));
+
+        assert_eq!(table.distance(&[0, 1, 2, 3])?, 0);
+        assert_eq!(table.find_swap_order(&[0, 1, 3, 2])?, vec![(2, 3)]);
+        assert!(matches!(
+            table.distance(&[1, 0, 2, 3]),
+            Err(LibError::Unsolvable)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_embedded_matches_build() -> Result<(), LibError> {
+        let table = LookupTable::embedded_3x3();
+        assert_eq!(table.distances.len(), 181_440);
+        assert_eq!(table, &LookupTable::build(3, 3)?);
+        assert_eq!(table.distances.iter().max(), Some(&31));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() -> Result<(), LibError> {
+        let table = LookupTable::build(2, 3)?;
+        assert_eq!(LookupTable::from_bytes(&table.to_bytes())?, table);
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_corruption() -> Result<(), LibError> {
+        let bytes = LookupTable::build(2, 3)?.to_bytes();
+
+        let mut corrupted = bytes.clone();
+        corrupted[HEADER_LEN + 5] ^= 1;
+        assert!(matches!(
+            LookupTable::from_bytes(&corrupted),
+            Err(LibError::ChecksumMismatch { .. })
+        ));
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = 2;
+        assert!(matches!(
+            LookupTable::from_bytes(&wrong_version),
+            Err(LibError::UnsupportedLookupTableVersion(2))
+        ));
+
+        assert!(matches!(
+            LookupTable::from_bytes(&bytes[..HEADER_LEN]),
+            Err(LibError::InvalidLookupTable(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsupported_size() {
+        assert!(matches!(
+            LookupTable::build(4, 4),
+            Err(LibError::UnsupportedSize {
+                width: 4,
+                height: 4
+            })
+        ));
+    }
+}
@@ -1,5 +1,23 @@
 //! Slide puzzle solver implementations
 
+pub mod beam_search;
 pub mod divide_and_conquer;
+pub mod generator;
+pub mod heuristic;
+pub mod hint;
+pub mod ida_star;
+pub mod lookup_table;
+pub mod move_metric;
+pub mod notation;
 pub mod optimal;
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+pub mod parallel_ida_star;
+pub mod pattern_database;
+pub mod peephole;
+pub mod progress;
+pub mod registry;
+mod state_key;
 pub(crate) mod test;
+pub mod verify;
+pub mod walking_distance;
+pub mod weighted_a_star;
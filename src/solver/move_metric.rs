@@ -0,0 +1,207 @@
+//! Metrics to count the moves of a solution
+//!
+//! In the single-tile metric, every swap of the empty field with a neighbour
+//! is a move. In the multi-tile metric, sliding several fields of a row or
+//! column towards the empty field at once is a single move, i.e. the empty
+//! field jumps to any position in its row or column. Such a move is a
+//! [`Slide`].
+//!
+//! Solutions are always expressed as swaps. A multi-tile solution is split
+//! into the swaps of its slides and [`to_slides`] merges consecutive swaps in
+//! the same direction again. A shortest multi-tile solution never has two
+//! consecutive slides along the same row or column, since they could be merged
+//! into one, so merging restores its slides.
+//!
+
+use crate::board::{get_idx_from_row_col, get_row_col_from_idx, in_bounds};
+
+/// Metric to count moves.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MoveMetric {
+    /// Every swap of the empty field with a neighbour is a move.
+    #[default]
+    SingleTile,
+    /// Sliding any number of fields of a row or column is a move.
+    MultiTile,
+}
+
+/// Move of the empty field along a row or column, which slides all fields in
+/// between by one position towards its previous position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Slide {
+    /// Index of the empty field before the slide.
+    pub from: usize,
+    /// Index of the empty field after the slide.
+    pub to: usize,
+}
+
+impl Slide {
+    /// Number of fields which move.
+    pub fn num_fields(&self, width: usize) -> usize {
+        match self.from / width == self.to / width {
+            true => self.from.abs_diff(self.to),
+            false => self.from.abs_diff(self.to) / width,
+        }
+    }
+
+    /// Get the slide which undoes this one.
+    pub fn reversed(&self) -> Self {
+        Self {
+            from: self.to,
+            to: self.from,
+        }
+    }
+
+    /// Split the slide into swaps of the empty field with a neighbour.
+    pub fn swaps(&self, width: usize) -> impl Iterator<Item = (usize, usize)> {
+        let step = match self.from / width == self.to / width {
+            true => 1,
+            false => width,
+        };
+        let from = self.from;
+        let num_fields = self.num_fields(width);
+        let forward = self.to > self.from;
+
+        (0..num_fields).map(move |i| {
+            let (before, after) = match forward {
+                true => (from + i * step, from + (i + 1) * step),
+                false => (from - i * step, from - (i + 1) * step),
+            };
+            (before, after)
+        })
+    }
+}
+
+/// Merge consecutive swaps in the same direction into slides.
+pub fn to_slides(swaps: &[(usize, usize)]) -> Vec<Slide> {
+    let mut slides: Vec<Slide> = Vec::new();
+    let mut prev_direction = None;
+
+    for &(empty_idx_before, empty_idx_after) in swaps {
+        let direction = empty_idx_after as isize - empty_idx_before as isize;
+        match slides.last_mut() {
+            Some(slide) if prev_direction == Some(direction) && slide.to == empty_idx_before => {
+                slide.to = empty_idx_after;
+            }
+            _ => slides.push(Slide {
+                from: empty_idx_before,
+                to: empty_idx_after,
+            }),
+        }
+        prev_direction = Some(direction);
+    }
+
+    slides
+}
+
+/// Count the moves of a swap sequence in a metric.
+pub fn count_moves(swaps: &[(usize, usize)], metric: MoveMetric) -> usize {
+    match metric {
+        MoveMetric::SingleTile => swaps.len(),
+        MoveMetric::MultiTile => to_slides(swaps).len(),
+    }
+}
+
+/// Get the indices the empty field can move to with one move of a metric.
+///
+/// The neighbours come first in the same order as the ones of
+/// [`crate::board::get_swappable_neighbours`], followed by the more distant
+/// positions.
+pub(crate) fn get_move_targets(
+    width: usize,
+    height: usize,
+    empty_field_idx: usize,
+    metric: MoveMetric,
+) -> impl Iterator<Item = usize> {
+    let (row, col) = get_row_col_from_idx(empty_field_idx, width);
+    let max_distance = match metric {
+        MoveMetric::SingleTile => 1,
+        MoveMetric::MultiTile => usize::max(width, height),
+    };
+
+    (1..=max_distance).flat_map(move |distance| {
+        [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .into_iter()
+            .filter_map(move |(delta_row, delta_col)| {
+                let target_row = row as isize + delta_row * distance as isize;
+                let target_col = col as isize + delta_col * distance as isize;
+                in_bounds(target_row, target_col, width as isize, height as isize)
+                    .then(|| get_idx_from_row_col(target_row, target_col, width as isize) as usize)
+            })
+    })
+}
+
+/// Apply a slide of the empty field to the fields.
+pub(crate) fn apply_slide(fields: &mut [u8], slide: Slide, width: usize) {
+    for (empty_idx_before, empty_idx_after) in slide.swaps(width) {
+        fields.swap(empty_idx_before, empty_idx_after);
+    }
+}
+
+/// Split moves of the empty field into swaps with a neighbour.
+pub(crate) fn expand_moves(moves: &[(usize, usize)], width: usize) -> Vec<(usize, usize)> {
+    moves
+        .iter()
+        .flat_map(|&(from, to)| Slide { from, to }.swaps(width))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::get_swappable_neighbours;
+
+    #[test]
+    fn test_slide_swaps() {
+        let slide = Slide { from: 2, to: 0 };
+        assert_eq!(slide.num_fields(3), 2);
+        assert_eq!(slide.swaps(3).collect::<Vec<_>>(), vec![(2, 1), (1, 0)]);
+
+        let slide = Slide { from: 1, to: 13 };
+        assert_eq!(slide.num_fields(4), 3);
+        assert_eq!(
+            slide.swaps(4).collect::<Vec<_>>(),
+            vec![(1, 5), (5, 9), (9, 13)]
+        );
+    }
+
+    #[test]
+    fn test_to_slides() {
+        let swaps = [(8, 7), (7, 6), (6, 3), (3, 0), (0, 1), (1, 0)];
+        assert_eq!(
+            to_slides(&swaps),
+            vec![
+                Slide { from: 8, to: 6 },
+                Slide { from: 6, to: 0 },
+                Slide { from: 0, to: 1 },
+                Slide { from: 1, to: 0 },
+            ]
+        );
+        assert_eq!(count_moves(&swaps, MoveMetric::SingleTile), 6);
+        assert_eq!(count_moves(&swaps, MoveMetric::MultiTile), 4);
+        assert_eq!(count_moves(&[], MoveMetric::MultiTile), 0);
+    }
+
+    #[test]
+    fn test_slides_roundtrip() {
+        let swaps = [(8, 7), (7, 6), (6, 3), (3, 0), (0, 1)];
+        let moves: Vec<_> = to_slides(&swaps)
+            .into_iter()
+            .map(|slide| (slide.from, slide.to))
+            .collect();
+        assert_eq!(expand_moves(&moves, 3), swaps);
+    }
+
+    #[test]
+    fn test_get_move_targets() {
+        let targets: Vec<_> = get_move_targets(3, 3, 4, MoveMetric::MultiTile).collect();
+        assert_eq!(targets, vec![1, 7, 3, 5]);
+
+        let targets: Vec<_> = get_move_targets(4, 3, 0, MoveMetric::MultiTile).collect();
+        assert_eq!(targets, vec![4, 1, 8, 2, 3]);
+
+        let targets: Vec<_> = get_move_targets(4, 3, 5, MoveMetric::SingleTile).collect();
+        let neighbours: Vec<_> = get_swappable_neighbours(4, 3, 5).collect();
+        assert_eq!(targets, neighbours);
+    }
+}
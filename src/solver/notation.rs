@@ -0,0 +1,326 @@
+//! Notation of swap sequences as directions
+//!
+//! Index pairs depend on the board width and are hard to read. The common
+//! notation of other solvers instead writes every swap as the direction in
+//! which a field slides into the empty field: `L`eft, `U`p, `R`ight or
+//! `D`own. So `L` moves the empty field one position to the right.
+//!
+//! The compact form writes runs of the same direction with their length, e.g.
+//! `L3U2` for `LLLUU`. Both forms can be parsed, and whitespace between the
+//! directions is ignored so that long solutions can be wrapped.
+//!
+
+use std::{iter::Peekable, str::CharIndices};
+
+use crate::{board::get_row_col_from_idx, error::LibError, solver::verify::check_swap};
+
+/// Direction in which a field slides into the empty field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Up,
+    Right,
+    Down,
+}
+
+impl Direction {
+    pub fn letter(&self) -> char {
+        match self {
+            Direction::Left => 'L',
+            Direction::Up => 'U',
+            Direction::Right => 'R',
+            Direction::Down => 'D',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<Self> {
+        match letter {
+            'L' => Some(Direction::Left),
+            'U' => Some(Direction::Up),
+            'R' => Some(Direction::Right),
+            'D' => Some(Direction::Down),
+            _ => None,
+        }
+    }
+
+    /// Get the direction of a swap of neighbours.
+    fn of_swap((empty_idx_before, empty_idx_after): (usize, usize), width: usize) -> Self {
+        match empty_idx_after {
+            idx if idx == empty_idx_before + 1 => Direction::Left,
+            idx if idx + 1 == empty_idx_before => Direction::Right,
+            idx if idx > empty_idx_before => Direction::Up,
+            _ => {
+                debug_assert_eq!(empty_idx_after + width, empty_idx_before);
+                Direction::Down
+            }
+        }
+    }
+
+    /// Get the index of the field which slides into the empty field, `None` if
+    /// there is no such field on the board.
+    fn empty_idx_after(
+        &self,
+        empty_field_idx: usize,
+        width: usize,
+        height: usize,
+    ) -> Option<usize> {
+        let (row, col) = get_row_col_from_idx(empty_field_idx, width);
+        match self {
+            Direction::Left => (col + 1 < width).then(|| empty_field_idx + 1),
+            Direction::Up => (row + 1 < height).then(|| empty_field_idx + width),
+            Direction::Right => (col > 0).then(|| empty_field_idx - 1),
+            Direction::Down => (row > 0).then(|| empty_field_idx - width),
+        }
+    }
+}
+
+/// Write swaps as one direction per swap, e.g. `LLURDR`.
+///
+/// Returns [`LibError::InvalidSwap`] if a swap is not a move of the empty
+/// field to a neighbour.
+pub fn format_swaps(
+    swaps: &[(usize, usize)],
+    width: usize,
+    height: usize,
+) -> Result<String, LibError> {
+    Ok(get_directions(swaps, width, height)?
+        .into_iter()
+        .map(|direction| direction.letter())
+        .collect())
+}
+
+/// Write swaps as directions with the length of every run of more than one
+/// swap in the same direction, e.g. `L2URDR`.
+///
+/// Returns [`LibError::InvalidSwap`] if a swap is not a move of the empty
+/// field to a neighbour.
+pub fn format_swaps_compact(
+    swaps: &[(usize, usize)],
+    width: usize,
+    height: usize,
+) -> Result<String, LibError> {
+    let directions = get_directions(swaps, width, height)?;
+
+    let mut notation = String::new();
+    for run in directions.chunk_by(|a, b| a == b) {
+        notation.push(run[0].letter());
+        if run.len() > 1 {
+            notation.push_str(&run.len().to_string());
+        }
+    }
+
+    Ok(notation)
+}
+
+/// Parse directions in the plain or compact form into swaps, starting with
+/// the empty field at `empty_field_idx`.
+///
+/// Returns [`LibError::InvalidNotation`] for anything but directions, counts
+/// and whitespace, and [`LibError::IllegalMove`] if no field can slide in a
+/// direction.
+pub fn parse_swaps(
+    notation: &str,
+    empty_field_idx: usize,
+    width: usize,
+    height: usize,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    let mut swaps = Vec::new();
+    let mut empty_field_idx = empty_field_idx;
+
+    let mut chars = notation.char_indices().peekable();
+    while let Some((position, letter)) = chars.next() {
+        if letter.is_whitespace() {
+            continue;
+        }
+
+        let direction = match Direction::from_letter(letter) {
+            Some(direction) => direction,
+            None if letter.is_ascii_digit() => {
+                return Err(LibError::InvalidNotation {
+                    position,
+                    reason: "count without a direction",
+                })
+            }
+            None => {
+                return Err(LibError::InvalidNotation {
+                    position,
+                    reason: "expected one of L, U, R and D",
+                })
+            }
+        };
+
+        for _ in 0..parse_count(&mut chars)? {
+            let empty_idx_after = direction
+                .empty_idx_after(empty_field_idx, width, height)
+                .ok_or(LibError::IllegalMove {
+                    step: swaps.len(),
+                    direction: letter,
+                    empty_field_idx,
+                })?;
+            swaps.push((empty_field_idx, empty_idx_after));
+            empty_field_idx = empty_idx_after;
+        }
+    }
+
+    Ok(swaps)
+}
+
+/// Get the direction of every swap after checking that the swaps form a path
+/// of the empty field.
+fn get_directions(
+    swaps: &[(usize, usize)],
+    width: usize,
+    height: usize,
+) -> Result<Vec<Direction>, LibError> {
+    let Some(&(mut empty_field_idx, _)) = swaps.first() else {
+        return Ok(Vec::new());
+    };
+
+    let mut directions = Vec::with_capacity(swaps.len());
+    for (step, &swap) in swaps.iter().enumerate() {
+        check_swap(empty_field_idx, swap, width, height)
+            .map_err(|reason| LibError::InvalidSwap { step, swap, reason })?;
+
+        directions.push(Direction::of_swap(swap, width));
+        empty_field_idx = swap.1;
+    }
+
+    Ok(directions)
+}
+
+/// Parse the optional count after a direction, which is 1 if it is missing.
+fn parse_count(chars: &mut Peekable<CharIndices>) -> Result<usize, LibError> {
+    let Some(&(start_position, _)) = chars.peek().filter(|(_, digit)| digit.is_ascii_digit())
+    else {
+        return Ok(1);
+    };
+
+    let mut count: usize = 0;
+    while let Some((_, digit)) = chars.next_if(|(_, digit)| digit.is_ascii_digit()) {
+        count = count
+            .checked_mul(10)
+            .and_then(|count| count.checked_add(digit as usize - '0' as usize))
+            .ok_or(LibError::InvalidNotation {
+                position: start_position,
+                reason: "count is too large",
+            })?;
+    }
+
+    match count {
+        0 => Err(LibError::InvalidNotation {
+            position: start_position,
+            reason: "count has to be positive",
+        }),
+        _ => Ok(count),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::InvalidSwapReason;
+
+    #[test]
+    fn test_format() -> Result<(), LibError> {
+        // Empty field in the bottom right corner of a 3x3 board
+        let swaps = [(8, 7), (7, 6), (6, 3), (3, 4), (4, 5), (5, 4)];
+        assert_eq!(format_swaps(&swaps, 3, 3)?, "RRDLLR");
+        assert_eq!(format_swaps_compact(&swaps, 3, 3)?, "R2DL2R");
+        assert_eq!(format_swaps(&[], 3, 3)?, "");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse() -> Result<(), LibError> {
+        let swaps = [(8, 7), (7, 6), (6, 3), (3, 4), (4, 5), (5, 4)];
+        assert_eq!(parse_swaps("RRDLLR", 8, 3, 3)?, swaps);
+        assert_eq!(parse_swaps("R2DL2R", 8, 3, 3)?, swaps);
+        assert_eq!(parse_swaps(" R2 D\nL2R ", 8, 3, 3)?, swaps);
+        assert_eq!(parse_swaps("", 8, 3, 3)?, []);
+
+        // Counts may have several digits
+        let swaps = parse_swaps("R11", 11, 12, 1)?;
+        assert_eq!(swaps.len(), 11);
+        assert_eq!(swaps.last(), Some(&(1, 0)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip_rectangular() -> Result<(), LibError> {
+        let swaps = [(11, 7), (7, 3), (3, 2), (2, 6), (6, 10), (10, 11)];
+        for notation in [
+            format_swaps(&swaps, 4, 3)?,
+            format_swaps_compact(&swaps, 4, 3)?,
+        ] {
+            assert_eq!(parse_swaps(&notation, 11, 4, 3)?, swaps);
+        }
+        assert_eq!(format_swaps_compact(&swaps, 4, 3)?, "D2RU2L");
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_notation() {
+        for (notation, expected_position) in [("RX", 1), ("3R", 0), ("R0", 1), ("r", 0)] {
+            match parse_swaps(notation, 8, 3, 3) {
+                Err(LibError::InvalidNotation { position, .. }) => {
+                    assert_eq!(position, expected_position, "{notation}")
+                }
+                result => panic!("unexpected result {result:?} for {notation}"),
+            }
+        }
+
+        assert!(matches!(
+            parse_swaps("R99999999999999999999999", 8, 3, 3),
+            Err(LibError::InvalidNotation {
+                position: 1,
+                reason: "count is too large"
+            })
+        ));
+    }
+
+    #[test]
+    fn test_illegal_moves() {
+        // No field right of the empty field in the bottom right corner
+        assert!(matches!(
+            parse_swaps("L", 8, 3, 3),
+            Err(LibError::IllegalMove {
+                step: 0,
+                direction: 'L',
+                empty_field_idx: 8
+            })
+        ));
+        assert!(matches!(
+            parse_swaps("R3", 8, 3, 3),
+            Err(LibError::IllegalMove {
+                step: 2,
+                direction: 'R',
+                empty_field_idx: 6
+            })
+        ));
+        assert!(matches!(
+            parse_swaps("DDD", 8, 3, 3),
+            Err(LibError::IllegalMove { step: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_format_invalid_swaps() {
+        // Index 3 is next to index 2 in memory but in the row below
+        assert!(matches!(
+            format_swaps(&[(2, 3)], 3, 3),
+            Err(LibError::InvalidSwap {
+                step: 0,
+                reason: InvalidSwapReason::NotNeighbour,
+                ..
+            })
+        ));
+        assert!(matches!(
+            format_swaps_compact(&[(8, 7), (8, 5)], 3, 3),
+            Err(LibError::InvalidSwap {
+                step: 1,
+                reason: InvalidSwapReason::NotEmptyField { empty_field_idx: 7 },
+                ..
+            })
+        ));
+    }
+}
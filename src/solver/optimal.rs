@@ -3,18 +3,31 @@
 //! This runs a breath-first-search in the state space of possible slides until
 //! finding the final state. The state space is built on the fly.
 //!
+//! The bidirectional variant searches from the initial and the final state at
+//! the same time and stitches the paths together where both searches meet.
+//! Since each search only has to go half the depth, far fewer states are
+//! visited.
+//!
+//! Both searches also find paths to arbitrary goal states instead of the
+//! solved state, see [`find_swap_order_to_goal`].
+//!
+//! Visited states are identified by an exact, packed key of their fields (see
+//! [`StateKey`]), so different states can never be mistaken for each other.
+//!
 
-use std::{
-    collections::VecDeque,
-    hash::{Hash, Hasher},
-};
+use std::collections::VecDeque;
 
-use fnv::FnvHasher;
 use rustc_hash::FxHashMap;
 
 use crate::{
-    board::{get_empty_field_idx, get_swappable_neighbours, initialize_fields},
+    board::{ensure_solvable, get_empty_field_idx, initialize_fields, is_goal_reachable},
     error::LibError,
+    solver::{
+        move_metric::{apply_slide, expand_moves, get_move_targets, MoveMetric, Slide},
+        progress::{NoProgress, ProgressObserver, ProgressReporter, REPORT_INTERVAL},
+        registry::{Puzzle, Solution, Solver, SolverInfo},
+        state_key::StateKey,
+    },
 };
 
 /// Find the swap order to solve a puzzle
@@ -29,148 +42,662 @@ pub fn find_swap_order(
     width: usize,
     height: usize,
     max_num_steps: usize,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    find_swap_order_with_progress(fields, width, height, max_num_steps, &mut NoProgress)
+}
+
+/// Find the swap order to solve a puzzle and report the progress.
+///
+/// The observer is called every few thousand expanded states with the current
+/// level of the search and may cancel it.
+pub fn find_swap_order_with_progress(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    max_num_steps: usize,
+    observer: &mut dyn ProgressObserver,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    find_swap_order_with_metric(
+        fields,
+        width,
+        height,
+        MoveMetric::SingleTile,
+        max_num_steps,
+        observer,
+    )
+}
+
+/// Find the swap order with the fewest moves in a metric and report the
+/// progress.
+///
+/// In the multi-tile metric, every slide counts as one move and is returned
+/// as the swaps it consists of, see [`crate::solver::move_metric`].
+pub fn find_swap_order_with_metric(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    metric: MoveMetric,
+    max_num_steps: usize,
+    observer: &mut dyn ProgressObserver,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    ensure_solvable(fields, width, height)?;
+
+    let goal = initialize_fields(fields.len())?;
+    let reporter = ProgressReporter::new(observer, REPORT_INTERVAL);
+    find_swap_order_between(
+        fields,
+        &goal,
+        width,
+        height,
+        metric,
+        max_num_steps,
+        reporter,
+    )
+}
+
+/// Find the swap order from a puzzle state to an arbitrary goal state.
+///
+/// The goal can be any arrangement of the same fields, e.g. with the empty
+/// field in the top left corner. Returns [`LibError::GoalUnreachable`] if no
+/// sequence of swaps leads to the goal.
+pub fn find_swap_order_to_goal(
+    fields: &[u8],
+    goal: &[u8],
+    width: usize,
+    height: usize,
+    max_num_steps: usize,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    if !is_goal_reachable(fields, goal, width)? {
+        return Err(LibError::GoalUnreachable);
+    }
+
+    let mut observer = NoProgress;
+    let reporter = ProgressReporter::new(&mut observer, REPORT_INTERVAL);
+    find_swap_order_between(
+        fields,
+        goal,
+        width,
+        height,
+        MoveMetric::SingleTile,
+        max_num_steps,
+        reporter,
+    )
+}
+
+fn find_swap_order_between(
+    fields: &[u8],
+    goal: &[u8],
+    width: usize,
+    height: usize,
+    metric: MoveMetric,
+    max_num_steps: usize,
+    reporter: ProgressReporter,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    if u64::fits(fields.len()) {
+        find_swap_order_with_key::<u64>(
+            fields,
+            goal,
+            width,
+            height,
+            metric,
+            max_num_steps,
+            reporter,
+        )
+    } else if u128::fits(fields.len()) {
+        find_swap_order_with_key::<u128>(
+            fields,
+            goal,
+            width,
+            height,
+            metric,
+            max_num_steps,
+            reporter,
+        )
+    } else {
+        find_swap_order_with_key::<Box<[u8]>>(
+            fields,
+            goal,
+            width,
+            height,
+            metric,
+            max_num_steps,
+            reporter,
+        )
+    }
+}
+
+fn find_swap_order_with_key<K: StateKey>(
+    fields: &[u8],
+    goal: &[u8],
+    width: usize,
+    height: usize,
+    metric: MoveMetric,
+    max_num_steps: usize,
+    mut reporter: ProgressReporter,
 ) -> Result<Vec<(usize, usize)>, LibError> {
     // Determine initial values
-    let fields = fields.to_owned();
-    let initial_hash = fields.hashed();
-    let target_fields = initialize_fields(fields.len());
-    let target_hash = target_fields.hashed();
+    let initial_key = K::pack(fields);
+    let target_key = K::pack(goal);
 
     // Exit early if the puzzle is already solved
-    if initial_hash == target_hash {
+    if initial_key == target_key {
         return Ok(Vec::with_capacity(0));
     }
 
-    let empty_field_idx = get_empty_field_idx(&fields)?;
-
-    // Map from a state hash to its parent hash and the last swap that led to
-    // this state from the parent. We need the swap information to trace back
-    // a path from the start to the target later.
-    let mut parent_map = FxHashMap::default();
-
-    // Hold tuples of (state, state_hash, parent_hash, last_swap)
+    let empty_field_idx = get_empty_field_idx(fields)?;
+
+    // Map from a state key to its parent key and the last move that led to
+    // this state from the parent. We need the move information to trace back
+    // a path from the start to the target later. States are added when they
+    // are discovered, so the first (shortest) path to a state is kept.
+    let mut parent_map: FxHashMap<K, (K, Slide)> = FxHashMap::default();
+    parent_map.insert(
+        initial_key.clone(),
+        // For the first state, the parent key and move are never used.
+        (
+            initial_key.clone(),
+            Slide {
+                from: empty_field_idx,
+                to: empty_field_idx,
+            },
+        ),
+    );
+
+    // Hold tuples of (state_key, empty_idx, depth)
     let mut states_to_explore = VecDeque::from([Step {
-        state: fields,
-        state_hash: initial_hash,
-        // For the first state, the parent hash is never used and can be set to zero.
-        parent_hash: 0,
-        // The empty field index is extracted from the last swap so we need to initialize it properly.
-        last_swap: Swap {
-            regular_idx: 0,
-            empty_idx: empty_field_idx,
-        },
+        state_key: initial_key.clone(),
+        empty_idx: empty_field_idx,
+        depth: 0,
     }]);
 
+    // Buffer for the fields of the current state
+    let mut state = fields.to_owned();
     let mut num_iterations = 0;
+    let mut target_found = false;
 
     // Get state information for unseen state
-    while let Some(step) = states_to_explore.pop_front() {
+    while let Some(Step {
+        state_key,
+        empty_idx,
+        depth,
+    }) = states_to_explore.pop_front()
+    {
         num_iterations += 1;
         if num_iterations > max_num_steps {
             return Err(LibError::MaxNumStepsReached(max_num_steps));
         }
+        reporter.update(num_iterations, depth)?;
 
-        let Step {
-            state,
-            state_hash,
-            parent_hash,
-            last_swap,
-        } = step;
-
-        // Add state hash with parent and last swap to map
-        parent_map.insert(state_hash, (parent_hash, last_swap.clone()));
-
-        // If the state is the target state, break
-        if state_hash == target_hash {
-            break;
-        }
+        state_key.unpack_into(&mut state);
 
         // Determine all reachable next states
-        let swappable_neighbours = get_swappable_neighbours(width, height, last_swap.empty_idx);
-        let unseen_neighbours = swappable_neighbours.filter_map(|neighbour_idx| {
-            let mut next_fields = state.clone();
-
-            // Swap fields to calculate hash and check if we already know the state.
-            next_fields.swap(neighbour_idx, last_swap.empty_idx);
-            let next_fields_hash = next_fields.hashed();
-
-            if parent_map.contains_key(&next_fields_hash) {
-                None
-            } else {
-                // After swapping the fields, the indices are reversed.
-                let next_swap = Swap {
-                    regular_idx: last_swap.empty_idx,
-                    empty_idx: neighbour_idx,
-                };
+        for target_idx in get_move_targets(width, height, empty_idx, metric) {
+            // Move the empty field to calculate the key and check if we
+            // already know the state.
+            let next_move = Slide {
+                from: empty_idx,
+                to: target_idx,
+            };
+            apply_slide(&mut state, next_move, width);
+            let next_key = K::pack(&state);
+            apply_slide(&mut state, next_move.reversed(), width);
+
+            if parent_map.contains_key(&next_key) {
+                continue;
+            }
 
-                Some(Step {
-                    state: next_fields,
-                    state_hash: next_fields_hash,
-                    parent_hash: state_hash,
-                    last_swap: next_swap,
-                })
+            parent_map.insert(next_key.clone(), (state_key.clone(), next_move));
+
+            // If the state is the target state, stop
+            if next_key == target_key {
+                target_found = true;
+                break;
             }
-        });
 
-        // Add information of unseen states to the queue to explore
-        states_to_explore.extend(unseen_neighbours);
+            // Add unseen states to the queue to explore
+            states_to_explore.push_back(Step {
+                state_key: next_key,
+                empty_idx: target_idx,
+                depth: depth + 1,
+            });
+        }
+
+        if target_found {
+            break;
+        }
     }
 
     log::debug!("Number of iterations in solver: {}", num_iterations);
 
     // Extract the path of swaps from the initial position to the target if it
     // exists
-    match parent_map.contains_key(&target_hash) {
+    match target_found {
         false => Err(LibError::TerminatedWithoutSolution),
         true => {
             // Trace back from target to beginning
-            let mut swaps = Vec::new();
+            let mut moves = Vec::new();
 
-            let mut next_hash = target_hash;
-            while let Some((parent_hash, swap)) = parent_map.get(&next_hash) {
-                swaps.push((swap.regular_idx, swap.empty_idx));
-                if *parent_hash == initial_hash {
-                    break;
-                }
+            let mut next_key = target_key;
+            while next_key != initial_key {
+                let (parent_key, slide) = &parent_map[&next_key];
+                moves.push((slide.from, slide.to));
+                next_key = parent_key.clone();
+            }
+            moves.reverse();
+
+            log::debug!("Number of moves to solve: {}", moves.len());
 
-                next_hash = *parent_hash;
+            Ok(expand_moves(&moves, width))
+        }
+    }
+}
+
+/// Find the swap order to solve a puzzle with a bidirectional search
+///
+/// We alternate between expanding a full level of the forward search from the
+/// initial state and the backward search from the final state, always taking
+/// the side with the smaller frontier. Once a newly discovered state is known
+/// to the other side, the level is completed and the shortest of all stitched
+/// paths is taken. The result is a shortest swap sequence just like the one of
+/// [`find_swap_order`]. `max_num_steps` limits the number of expanded states of
+/// both sides together, like the one of [`find_swap_order`].
+pub fn find_swap_order_bidirectional(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    max_num_steps: usize,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    find_swap_order_bidirectional_with_progress(
+        fields,
+        width,
+        height,
+        max_num_steps,
+        &mut NoProgress,
+    )
+}
+
+/// Find the swap order with a bidirectional search and report the progress.
+///
+/// The observer is called after expanded levels with the sum of the depths of
+/// both sides and may cancel the search.
+pub fn find_swap_order_bidirectional_with_progress(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    max_num_steps: usize,
+    observer: &mut dyn ProgressObserver,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    find_swap_order_bidirectional_with_metric(
+        fields,
+        width,
+        height,
+        MoveMetric::SingleTile,
+        max_num_steps,
+        observer,
+    )
+}
+
+/// Find the swap order with the fewest moves in a metric with a bidirectional
+/// search and report the progress, see [`find_swap_order_with_metric`].
+pub fn find_swap_order_bidirectional_with_metric(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    metric: MoveMetric,
+    max_num_steps: usize,
+    observer: &mut dyn ProgressObserver,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    ensure_solvable(fields, width, height)?;
+
+    let goal = initialize_fields(fields.len())?;
+    let reporter = ProgressReporter::new(observer, REPORT_INTERVAL);
+    find_swap_order_bidirectional_between(
+        fields,
+        &goal,
+        width,
+        height,
+        metric,
+        max_num_steps,
+        reporter,
+    )
+}
+
+/// Find the swap order from a puzzle state to an arbitrary goal state with a
+/// bidirectional search, see [`find_swap_order_to_goal`].
+pub fn find_swap_order_bidirectional_to_goal(
+    fields: &[u8],
+    goal: &[u8],
+    width: usize,
+    height: usize,
+    max_num_steps: usize,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    if !is_goal_reachable(fields, goal, width)? {
+        return Err(LibError::GoalUnreachable);
+    }
+
+    let mut observer = NoProgress;
+    let reporter = ProgressReporter::new(&mut observer, REPORT_INTERVAL);
+    find_swap_order_bidirectional_between(
+        fields,
+        goal,
+        width,
+        height,
+        MoveMetric::SingleTile,
+        max_num_steps,
+        reporter,
+    )
+}
+
+fn find_swap_order_bidirectional_between(
+    fields: &[u8],
+    goal: &[u8],
+    width: usize,
+    height: usize,
+    metric: MoveMetric,
+    max_num_steps: usize,
+    reporter: ProgressReporter,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    if u64::fits(fields.len()) {
+        find_swap_order_bidirectional_with_key::<u64>(
+            fields,
+            goal,
+            width,
+            height,
+            metric,
+            max_num_steps,
+            reporter,
+        )
+    } else if u128::fits(fields.len()) {
+        find_swap_order_bidirectional_with_key::<u128>(
+            fields,
+            goal,
+            width,
+            height,
+            metric,
+            max_num_steps,
+            reporter,
+        )
+    } else {
+        find_swap_order_bidirectional_with_key::<Box<[u8]>>(
+            fields,
+            goal,
+            width,
+            height,
+            metric,
+            max_num_steps,
+            reporter,
+        )
+    }
+}
+
+fn find_swap_order_bidirectional_with_key<K: StateKey>(
+    fields: &[u8],
+    goal: &[u8],
+    width: usize,
+    height: usize,
+    metric: MoveMetric,
+    max_num_steps: usize,
+    mut reporter: ProgressReporter,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    // Exit early if the puzzle is already solved
+    if fields == goal {
+        return Ok(Vec::with_capacity(0));
+    }
+
+    let empty_field_idx = get_empty_field_idx(fields)?;
+    let goal_empty_field_idx = get_empty_field_idx(goal)?;
+
+    let mut forward = SearchSide::new(K::pack(fields), empty_field_idx, width, height, metric);
+    let mut backward = SearchSide::new(K::pack(goal), goal_empty_field_idx, width, height, metric);
+
+    // Buffer for the fields of the state being expanded
+    let mut state = goal.to_owned();
+    let mut num_iterations = 0;
+
+    let (meeting_key, num_moves) = loop {
+        if forward.frontier.is_empty() || backward.frontier.is_empty() {
+            return Err(LibError::TerminatedWithoutSolution);
+        }
+
+        let meeting = match forward.frontier.len() <= backward.frontier.len() {
+            true => {
+                forward.expand_level(&backward, &mut state, &mut num_iterations, max_num_steps)?
             }
+            false => {
+                backward.expand_level(&forward, &mut state, &mut num_iterations, max_num_steps)?
+            }
+        };
+        reporter.update(num_iterations, forward.depth + backward.depth)?;
 
-            log::debug!("Number of swaps to solve: {}", swaps.len());
+        if let Some(meeting) = meeting {
+            break meeting;
+        }
+    };
 
-            Ok(swaps.into_iter().rev().collect())
+    log::debug!("Number of iterations in solver: {}", num_iterations);
+    log::debug!("Number of moves to solve: {}", num_moves);
+
+    // Trace back from the meeting state to the initial state and reverse
+    let mut moves = forward.trace_back(meeting_key.clone());
+    moves.reverse();
+
+    // Trace back from the meeting state to the final state. The moves were
+    // found from the final state, so we undo them in the order we trace them.
+    moves.extend(
+        backward
+            .trace_back(meeting_key)
+            .into_iter()
+            .map(|(empty_idx_before, empty_idx_after)| (empty_idx_after, empty_idx_before)),
+    );
+
+    Ok(expand_moves(&moves, width))
+}
+
+/// Breadth-first-search solver, see [`find_swap_order`].
+#[derive(Clone, Debug)]
+pub struct BreadthFirstSearch {
+    pub max_num_steps: usize,
+    pub move_metric: MoveMetric,
+}
+
+impl Solver for BreadthFirstSearch {
+    fn info(&self) -> SolverInfo {
+        SolverInfo {
+            name: "bfs",
+            optimal: true,
+            metric: self.move_metric,
+            square_only: false,
+            min_size: 2,
+            max_num_fields: 16,
         }
     }
+
+    fn solve_with_progress(
+        &self,
+        puzzle: &Puzzle,
+        observer: &mut dyn ProgressObserver,
+    ) -> Result<Solution, LibError> {
+        let swaps = find_swap_order_with_metric(
+            &puzzle.fields,
+            puzzle.width,
+            puzzle.height,
+            self.move_metric,
+            self.max_num_steps,
+            observer,
+        )?;
+        Ok(Solution { swaps })
+    }
 }
 
-trait Hashed<T> {
-    fn hashed(&self) -> u64;
+/// Bidirectional breadth-first-search solver, see
+/// [`find_swap_order_bidirectional`].
+#[derive(Clone, Debug)]
+pub struct BidirectionalSearch {
+    pub max_num_steps: usize,
+    pub move_metric: MoveMetric,
 }
 
-impl<T> Hashed<T> for Vec<T>
-where
-    T: std::hash::Hash,
-{
-    fn hashed(&self) -> u64 {
-        // FnvHasher has a lower collision probability than FxHasher and we are
-        // hashing up to millions of states
-        let mut s = FnvHasher::with_key(1234);
-        self.hash(&mut s);
-        s.finish()
+impl Solver for BidirectionalSearch {
+    fn info(&self) -> SolverInfo {
+        SolverInfo {
+            name: "bidirectional",
+            optimal: true,
+            metric: self.move_metric,
+            square_only: false,
+            min_size: 2,
+            max_num_fields: 25,
+        }
+    }
+
+    fn solve_with_progress(
+        &self,
+        puzzle: &Puzzle,
+        observer: &mut dyn ProgressObserver,
+    ) -> Result<Solution, LibError> {
+        let swaps = find_swap_order_bidirectional_with_metric(
+            &puzzle.fields,
+            puzzle.width,
+            puzzle.height,
+            self.move_metric,
+            self.max_num_steps,
+            observer,
+        )?;
+        Ok(Solution { swaps })
     }
 }
 
-struct Step {
-    state: Vec<u8>,
-    state_hash: u64,
-    parent_hash: u64,
-    last_swap: Swap,
+/// One side of a bidirectional search.
+struct SearchSide<K> {
+    /// Map from a state key to its parent key, the move that led to this
+    /// state from the parent and its depth.
+    visited: FxHashMap<K, Visit<K>>,
+    /// States of the deepest level with their empty field index.
+    frontier: Vec<(K, usize)>,
+    /// Depth of the frontier.
+    depth: usize,
+    width: usize,
+    height: usize,
+    metric: MoveMetric,
+}
+
+struct Visit<K> {
+    parent_key: K,
+    /// Empty field index before and after the move.
+    swap: (usize, usize),
+    depth: usize,
 }
 
-#[derive(Clone)]
-struct Swap {
-    regular_idx: usize,
+impl<K: StateKey> SearchSide<K> {
+    fn new(
+        key: K,
+        empty_field_idx: usize,
+        width: usize,
+        height: usize,
+        metric: MoveMetric,
+    ) -> Self {
+        let visited = FxHashMap::from_iter([(
+            key.clone(),
+            Visit {
+                // For the first state, the parent key and swap are never used.
+                parent_key: key.clone(),
+                swap: (empty_field_idx, empty_field_idx),
+                depth: 0,
+            },
+        )]);
+
+        Self {
+            visited,
+            frontier: vec![(key, empty_field_idx)],
+            depth: 0,
+            width,
+            height,
+            metric,
+        }
+    }
+
+    /// Expand all states of the frontier by one level.
+    ///
+    /// `state` is a buffer for the unpacked fields. `num_iterations` counts the
+    /// expanded states of both sides, and we return
+    /// [`LibError::MaxNumStepsReached`] before expanding a state beyond
+    /// `max_num_steps`. Returns the key of the meeting state on the shortest
+    /// path through both sides and the length of this path if the sides met.
+    fn expand_level(
+        &mut self,
+        other: &SearchSide<K>,
+        state: &mut [u8],
+        num_iterations: &mut usize,
+        max_num_steps: usize,
+    ) -> Result<Option<(K, usize)>, LibError> {
+        let mut meeting: Option<(K, usize)> = None;
+        let mut next_frontier = Vec::new();
+
+        for (state_key, empty_idx) in std::mem::take(&mut self.frontier) {
+            *num_iterations += 1;
+            if *num_iterations > max_num_steps {
+                return Err(LibError::MaxNumStepsReached(max_num_steps));
+            }
+
+            state_key.unpack_into(state);
+
+            for neighbour_idx in get_move_targets(self.width, self.height, empty_idx, self.metric) {
+                let slide = Slide {
+                    from: empty_idx,
+                    to: neighbour_idx,
+                };
+                apply_slide(state, slide, self.width);
+                let next_key = K::pack(state);
+                apply_slide(state, slide.reversed(), self.width);
+
+                if self.visited.contains_key(&next_key) {
+                    continue;
+                }
+
+                self.visited.insert(
+                    next_key.clone(),
+                    Visit {
+                        parent_key: state_key.clone(),
+                        swap: (empty_idx, neighbour_idx),
+                        depth: self.depth + 1,
+                    },
+                );
+
+                // Keep the shortest path through a state known to the other side
+                if let Some(other_visit) = other.visited.get(&next_key) {
+                    let num_moves = self.depth + 1 + other_visit.depth;
+                    if meeting.as_ref().is_none_or(|(_, best)| num_moves < *best) {
+                        meeting = Some((next_key.clone(), num_moves));
+                    }
+                }
+
+                next_frontier.push((next_key, neighbour_idx));
+            }
+        }
+
+        self.frontier = next_frontier;
+        self.depth += 1;
+
+        Ok(meeting)
+    }
+
+    /// Collect the moves from a state back to the start of this side.
+    fn trace_back(&self, mut key: K) -> Vec<(usize, usize)> {
+        let mut swaps = Vec::new();
+        while let Some(visit) = self.visited.get(&key) {
+            if visit.depth == 0 {
+                break;
+            }
+            swaps.push(visit.swap);
+            key = visit.parent_key.clone();
+        }
+        swaps
+    }
+}
+
+struct Step<K> {
+    state_key: K,
     empty_idx: usize,
+    depth: usize,
 }
 
 #[cfg(test)]
@@ -201,4 +728,101 @@ mod test {
         assert_eq!(swap_order, vec![(0, 3), (3, 4), (4, 7), (7, 8)]);
         Ok(())
     }
+
+    #[test]
+    fn test_bidirectional_zero_moves() -> Result<(), LibError> {
+        let fields = vec![0, 1, 2, 3];
+        let swap_order = find_swap_order_bidirectional(&fields, 2, 2, 10)?;
+        assert_eq!(swap_order, Vec::with_capacity(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bidirectional_one_move() -> Result<(), LibError> {
+        let fields = vec![0, 1, 3, 2];
+        let swap_order = find_swap_order_bidirectional(&fields, 2, 2, 10)?;
+        assert_eq!(swap_order, vec![(2, 3)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bidirectional_four_swaps() -> Result<(), LibError> {
+        let fields = vec![8, 1, 2, 0, 3, 5, 6, 4, 7];
+        let swap_order = find_swap_order_bidirectional(&fields, 3, 3, 256)?;
+        assert_eq!(swap_order, vec![(0, 3), (3, 4), (4, 7), (7, 8)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bidirectional_unsolvable() {
+        let fields = vec![1, 0, 2, 3];
+        assert!(matches!(
+            find_swap_order_bidirectional(&fields, 2, 2, 1000),
+            Err(LibError::Unsolvable)
+        ));
+        // The parity is checked before the search, so the limit does not matter
+        assert!(matches!(
+            find_swap_order_bidirectional(&fields, 2, 2, 4),
+            Err(LibError::Unsolvable)
+        ));
+        assert!(matches!(
+            find_swap_order(&fields, 2, 2, 0),
+            Err(LibError::Unsolvable)
+        ));
+    }
+
+    #[test]
+    fn test_max_num_steps() {
+        type Search = fn(&[u8], usize, usize, usize) -> Result<Vec<(usize, usize)>, LibError>;
+
+        // 14 swaps from the solved state
+        let fields = [3, 4, 0, 6, 2, 5, 7, 1, 8];
+        for search in [find_swap_order as Search, find_swap_order_bidirectional] {
+            for max_num_steps in [0, 1, 10] {
+                assert!(matches!(
+                    search(&fields, 3, 3, max_num_steps),
+                    Err(LibError::MaxNumStepsReached(n)) if n == max_num_steps
+                ));
+            }
+
+            // Both searches fail exactly one expansion before they succeed
+            let (mut low, mut high) = (0, 1_000_000);
+            while low < high {
+                let mid = (low + high) / 2;
+                match search(&fields, 3, 3, mid) {
+                    Ok(_) => high = mid,
+                    Err(LibError::MaxNumStepsReached(n)) if n == mid => low = mid + 1,
+                    Err(err) => panic!("unexpected error {err}"),
+                }
+            }
+            assert_eq!(search(&fields, 3, 3, low).unwrap().len(), 14);
+            assert!(matches!(
+                search(&fields, 3, 3, low - 1),
+                Err(LibError::MaxNumStepsReached(n)) if n == low - 1
+            ));
+        }
+    }
+
+    #[test]
+    fn test_large_boards() -> Result<(), LibError> {
+        // 18 fields are packed into a u128 and 26 fields into bytes
+        for (width, height) in [(6, 3), (13, 2)] {
+            let mut fields = initialize_fields(width * height).unwrap();
+            let empty_idx = fields.len() - 1;
+            let swaps = [
+                (empty_idx, empty_idx - width),
+                (empty_idx - width, empty_idx - width - 1),
+                (empty_idx - width - 1, empty_idx - 1),
+            ];
+            for (empty_idx_before, empty_idx_after) in swaps {
+                fields.swap(empty_idx_before, empty_idx_after);
+            }
+
+            let swap_order = find_swap_order(&fields, width, height, 10_000)?;
+            assert_eq!(swap_order.len(), 3);
+            let swap_order = find_swap_order_bidirectional(&fields, width, height, 10_000)?;
+            assert_eq!(swap_order.len(), 3);
+        }
+        Ok(())
+    }
 }
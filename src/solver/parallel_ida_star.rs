@@ -0,0 +1,567 @@
+//! Multi-threaded optimal puzzle solver based on IDA*
+//!
+//! Every iteration of IDA* (see [`crate::solver::ida_star`]) first expands the
+//! initial state a few levels deep. The states of the last level are the roots
+//! of the subtrees, which the worker threads take from a shared queue and
+//! search depth-first with the bound of the iteration.
+//!
+//! The subtrees are queued in the order in which the single-threaded search
+//! visits them. Once a solution is found, only subtrees in front of it are
+//! searched further, and the solution of the first subtree wins. The result is
+//! therefore the same shortest solution as the one of the single-threaded
+//! search, independent of the number of threads.
+//!
+//! This module is only available with the `parallel` feature on native
+//! targets, the web page keeps using the single-threaded solvers.
+//!
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    board::{ensure_solvable, get_empty_field_idx, get_swappable_neighbours, initialize_fields},
+    error::LibError,
+    solver::{
+        heuristic::{Heuristic, ManhattanDistance},
+        ida_star::SearchOutcome,
+        move_metric::MoveMetric,
+        pattern_database::{select_heuristic, PatternDatabase},
+        progress::{NoProgress, ProgressObserver, ProgressReporter, REPORT_INTERVAL},
+        registry::{Puzzle, Solution, Solver, SolverInfo},
+    },
+};
+
+/// Number of subtrees per thread, more subtrees balance the work better.
+const NUM_SUBTREES_PER_THREAD: usize = 16;
+
+/// Number of states a worker expands before it updates the shared counter and
+/// checks whether it should stop.
+const FLUSH_INTERVAL: usize = 1024;
+
+/// Time between two progress reports while the workers are running.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Find the swap order to solve a puzzle on all available cores.
+///
+/// The search is guided by the Manhattan distance, see
+/// [`find_swap_order_with_progress`].
+pub fn find_swap_order(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    max_num_steps: usize,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    let heuristic = ManhattanDistance::new(width, height);
+    find_swap_order_with_progress(
+        fields,
+        width,
+        height,
+        max_num_steps,
+        default_num_threads(),
+        &heuristic,
+        &mut NoProgress,
+    )
+    .map(|outcome| outcome.swaps)
+}
+
+/// Find the swap order with `num_threads` threads guided by a given heuristic
+/// and report the progress.
+///
+/// `max_num_steps` limits the number of expanded states of all threads, which
+/// count in batches, so the search may expand a few thousand states more per
+/// thread before it stops. The observer is called from the calling thread
+/// with the bound of the current iteration and may cancel the search.
+pub fn find_swap_order_with_progress<H: Heuristic + Sync>(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    max_num_steps: usize,
+    num_threads: usize,
+    heuristic: &H,
+    observer: &mut dyn ProgressObserver,
+) -> Result<SearchOutcome, LibError> {
+    ensure_solvable(fields, width, height)?;
+    let empty_field_idx = get_empty_field_idx(fields)?;
+    let goal = initialize_fields(fields.len())?;
+    let num_threads = num_threads.max(1);
+
+    let mut reporter = ProgressReporter::new(observer, REPORT_INTERVAL);
+    let num_expanded = AtomicUsize::new(0);
+
+    let root = Subtree {
+        fields: fields.to_owned(),
+        empty_field_idx,
+        prev_empty_field_idx: None,
+        swaps: Vec::new(),
+    };
+
+    let mut bound = heuristic.estimate(fields);
+    loop {
+        log::debug!("Searching with bound {} on {} threads", bound, num_threads);
+
+        let mut frontier = Frontier {
+            goal: &goal,
+            width,
+            height,
+            heuristic,
+            subtrees: Vec::new(),
+            next_bound: usize::MAX,
+            num_expanded: 0,
+        };
+        frontier.split(root.clone(), bound, num_threads * NUM_SUBTREES_PER_THREAD);
+        if num_expanded.fetch_add(frontier.num_expanded, Ordering::Relaxed) + frontier.num_expanded
+            > max_num_steps
+        {
+            return Err(LibError::MaxNumStepsReached(max_num_steps));
+        }
+
+        let iteration = Iteration {
+            subtrees: frontier.subtrees,
+            goal: &goal,
+            width,
+            height,
+            heuristic,
+            bound,
+            max_num_steps,
+            next_subtree: AtomicUsize::new(0),
+            solution_subtree: AtomicUsize::new(usize::MAX),
+            solution: Mutex::new(None),
+            next_bound: AtomicUsize::new(frontier.next_bound),
+            num_expanded: &num_expanded,
+            stop: AtomicBool::new(false),
+        };
+        iteration.run(num_threads, &mut reporter)?;
+
+        if let Some(FoundSolution { swaps, .. }) =
+            iteration.solution.into_inner().expect("no worker panicked")
+        {
+            let num_expanded = num_expanded.load(Ordering::Relaxed);
+            log::debug!("Number of iterations in solver: {}", num_expanded);
+            log::debug!("Number of swaps to solve: {}", swaps.len());
+
+            return Ok(SearchOutcome {
+                swaps,
+                num_expanded,
+            });
+        }
+        bound = iteration.next_bound.load(Ordering::Relaxed);
+    }
+}
+
+/// Number of threads which can run in parallel on this machine.
+pub fn default_num_threads() -> usize {
+    thread::available_parallelism().map_or(1, |num_threads| num_threads.get())
+}
+
+/// Multi-threaded IDA* solver, see [`find_swap_order_with_progress`].
+///
+/// The search is guided by the pattern database, see [`select_heuristic`], or
+/// by the Manhattan distance.
+#[derive(Clone, Debug)]
+pub struct ParallelIdaStar {
+    pub max_num_steps: usize,
+    pub num_threads: usize,
+    pub pattern_database: Option<Arc<PatternDatabase>>,
+}
+
+impl Solver for ParallelIdaStar {
+    fn info(&self) -> SolverInfo {
+        SolverInfo {
+            name: "parallel-ida-star",
+            optimal: true,
+            metric: MoveMetric::SingleTile,
+            square_only: false,
+            min_size: 2,
+            max_num_fields: 25,
+        }
+    }
+
+    fn solve_with_progress(
+        &self,
+        puzzle: &Puzzle,
+        observer: &mut dyn ProgressObserver,
+    ) -> Result<Solution, LibError> {
+        let Puzzle {
+            fields,
+            width,
+            height,
+        } = puzzle;
+
+        let heuristic = select_heuristic(
+            self.pattern_database.as_deref(),
+            *width,
+            *height,
+            ManhattanDistance::new(*width, *height),
+        );
+        let outcome = find_swap_order_with_progress(
+            fields,
+            *width,
+            *height,
+            self.max_num_steps,
+            self.num_threads,
+            &heuristic,
+            observer,
+        )?;
+
+        Ok(Solution {
+            swaps: outcome.swaps,
+        })
+    }
+}
+
+/// Root of a subtree, reached from the initial state by `swaps`.
+#[derive(Clone)]
+struct Subtree {
+    fields: Vec<u8>,
+    empty_field_idx: usize,
+    prev_empty_field_idx: Option<usize>,
+    swaps: Vec<(usize, usize)>,
+}
+
+/// Expansion of the initial state into subtrees for the workers.
+struct Frontier<'a, H> {
+    goal: &'a [u8],
+    width: usize,
+    height: usize,
+    heuristic: &'a H,
+    subtrees: Vec<Subtree>,
+    /// Smallest estimate which exceeded the bound while expanding.
+    next_bound: usize,
+    num_expanded: usize,
+}
+
+impl<H: Heuristic> Frontier<'_, H> {
+    /// Expand the initial state one level deeper at a time until there are at
+    /// least `min_num_subtrees` subtrees.
+    ///
+    /// States exceeding the bound are dropped and solutions are kept as
+    /// subtrees of their own, so the order of the subtrees matches the order
+    /// of a depth-first search.
+    fn split(&mut self, root: Subtree, bound: usize, min_num_subtrees: usize) {
+        let mut depth = 0;
+        loop {
+            self.subtrees.clear();
+            self.next_bound = usize::MAX;
+            self.num_expanded = 0;
+            let is_complete = self.expand(root.clone(), bound, depth);
+
+            if is_complete || self.subtrees.len() >= min_num_subtrees {
+                break;
+            }
+            depth += 1;
+        }
+    }
+
+    /// Collect the subtrees `depth` levels below a state and return whether
+    /// no state below was cut off by the depth.
+    fn expand(&mut self, subtree: Subtree, bound: usize, depth: usize) -> bool {
+        self.num_expanded += 1;
+
+        let estimate = subtree.swaps.len() + self.heuristic.estimate(&subtree.fields);
+        if estimate > bound {
+            self.next_bound = self.next_bound.min(estimate);
+            return true;
+        }
+
+        if subtree.fields == self.goal {
+            self.subtrees.push(subtree);
+            return true;
+        }
+        if depth == 0 {
+            self.subtrees.push(subtree);
+            return false;
+        }
+
+        let mut is_complete = true;
+        let empty_field_idx = subtree.empty_field_idx;
+        for neighbour_idx in get_swappable_neighbours(self.width, self.height, empty_field_idx) {
+            if Some(neighbour_idx) == subtree.prev_empty_field_idx {
+                continue;
+            }
+
+            let mut child = subtree.clone();
+            child.fields.swap(empty_field_idx, neighbour_idx);
+            child.swaps.push((empty_field_idx, neighbour_idx));
+            child.empty_field_idx = neighbour_idx;
+            child.prev_empty_field_idx = Some(empty_field_idx);
+
+            is_complete &= self.expand(child, bound, depth - 1);
+        }
+        is_complete
+    }
+}
+
+/// State of one IDA* iteration shared by all workers.
+struct Iteration<'a, H> {
+    subtrees: Vec<Subtree>,
+    goal: &'a [u8],
+    width: usize,
+    height: usize,
+    heuristic: &'a H,
+    bound: usize,
+    max_num_steps: usize,
+    /// Index of the next subtree to search.
+    next_subtree: AtomicUsize,
+    /// Index of the first subtree with a solution so far.
+    solution_subtree: AtomicUsize,
+    solution: Mutex<Option<FoundSolution>>,
+    /// Smallest estimate which exceeded the bound in any subtree.
+    next_bound: AtomicUsize,
+    /// Number of expanded states of all iterations.
+    num_expanded: &'a AtomicUsize,
+    /// Set if a worker failed or the observer cancelled the search.
+    stop: AtomicBool,
+}
+
+impl<H: Heuristic + Sync> Iteration<'_, H> {
+    /// Search all subtrees on `num_threads` threads and report the progress
+    /// until all of them are done.
+    fn run(&self, num_threads: usize, reporter: &mut ProgressReporter) -> Result<(), LibError> {
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..num_threads)
+                .map(|_| scope.spawn(|| self.work()))
+                .collect();
+
+            let mut result = Ok(());
+            while workers.iter().any(|worker| !worker.is_finished()) {
+                thread::sleep(POLL_INTERVAL);
+                if result.is_ok() {
+                    result = reporter.update(self.num_expanded.load(Ordering::Relaxed), self.bound);
+                    if result.is_err() {
+                        self.stop.store(true, Ordering::Relaxed);
+                    }
+                }
+            }
+
+            for worker in workers {
+                let worker_result = worker.join().expect("worker should not panic");
+                if result.is_ok() {
+                    result = worker_result;
+                }
+            }
+            result
+        })
+    }
+
+    /// Search subtrees until all of them are taken, a solution is found in
+    /// front of them or the search is stopped.
+    fn work(&self) -> Result<(), LibError> {
+        loop {
+            let subtree_idx = self.next_subtree.fetch_add(1, Ordering::Relaxed);
+            if subtree_idx >= self.subtrees.len()
+                || subtree_idx > self.solution_subtree.load(Ordering::Relaxed)
+            {
+                return Ok(());
+            }
+
+            let subtree = &self.subtrees[subtree_idx];
+            let mut worker = Worker {
+                iteration: self,
+                subtree_idx,
+                fields: subtree.fields.clone(),
+                swaps: subtree.swaps.clone(),
+                num_unflushed: 0,
+            };
+            let result = worker.search(
+                subtree.empty_field_idx,
+                subtree.prev_empty_field_idx,
+                self.bound,
+            );
+            worker.flush()?;
+
+            match result {
+                Ok(Bounded::Found) => {
+                    self.solution_subtree
+                        .fetch_min(subtree_idx, Ordering::Relaxed);
+                    let mut solution = self.solution.lock().expect("no worker panicked");
+                    if solution
+                        .as_ref()
+                        .is_none_or(|found| subtree_idx < found.subtree_idx)
+                    {
+                        *solution = Some(FoundSolution {
+                            subtree_idx,
+                            swaps: worker.swaps,
+                        });
+                    }
+                }
+                Ok(Bounded::Exceeded(estimate)) => {
+                    self.next_bound.fetch_min(estimate, Ordering::Relaxed);
+                }
+                Ok(Bounded::Abandoned) => {}
+                Err(error) => {
+                    self.stop.store(true, Ordering::Relaxed);
+                    return Err(error);
+                }
+            }
+        }
+    }
+}
+
+/// Solution found in a subtree.
+struct FoundSolution {
+    subtree_idx: usize,
+    swaps: Vec<(usize, usize)>,
+}
+
+/// Result of a bounded depth-first search of a worker.
+enum Bounded {
+    /// The goal state was reached, the path is stored in `Worker::swaps`.
+    Found,
+    /// The goal was not reached, holds the smallest exceeding estimate.
+    Exceeded(usize),
+    /// A solution was found in a subtree in front of this one, or the search
+    /// was stopped.
+    Abandoned,
+}
+
+/// Depth-first search of a single subtree.
+struct Worker<'i, 'a, H> {
+    iteration: &'i Iteration<'a, H>,
+    subtree_idx: usize,
+    fields: Vec<u8>,
+    swaps: Vec<(usize, usize)>,
+    /// Number of expanded states not yet added to the shared counter.
+    num_unflushed: usize,
+}
+
+impl<H: Heuristic + Sync> Worker<'_, '_, H> {
+    /// Run a depth-first search below the current state limited by `bound`,
+    /// like [`crate::solver::ida_star`] does.
+    fn search(
+        &mut self,
+        empty_field_idx: usize,
+        prev_empty_field_idx: Option<usize>,
+        bound: usize,
+    ) -> Result<Bounded, LibError> {
+        self.num_unflushed += 1;
+        if self.num_unflushed >= FLUSH_INTERVAL && !self.flush()? {
+            return Ok(Bounded::Abandoned);
+        }
+
+        let estimate = self.swaps.len() + self.iteration.heuristic.estimate(&self.fields);
+        if estimate > bound {
+            return Ok(Bounded::Exceeded(estimate));
+        }
+
+        if self.fields == self.iteration.goal {
+            return Ok(Bounded::Found);
+        }
+
+        let mut next_bound = usize::MAX;
+        for neighbour_idx in
+            get_swappable_neighbours(self.iteration.width, self.iteration.height, empty_field_idx)
+        {
+            if Some(neighbour_idx) == prev_empty_field_idx {
+                continue;
+            }
+
+            self.fields.swap(empty_field_idx, neighbour_idx);
+            self.swaps.push((empty_field_idx, neighbour_idx));
+
+            match self.search(neighbour_idx, Some(empty_field_idx), bound)? {
+                Bounded::Found => return Ok(Bounded::Found),
+                Bounded::Exceeded(estimate) => next_bound = next_bound.min(estimate),
+                Bounded::Abandoned => return Ok(Bounded::Abandoned),
+            }
+
+            self.swaps.pop();
+            self.fields.swap(empty_field_idx, neighbour_idx);
+        }
+
+        Ok(Bounded::Exceeded(next_bound))
+    }
+
+    /// Add the expanded states to the shared counter and return whether the
+    /// search of this subtree should continue.
+    fn flush(&mut self) -> Result<bool, LibError> {
+        let iteration = self.iteration;
+        let num_expanded = iteration
+            .num_expanded
+            .fetch_add(self.num_unflushed, Ordering::Relaxed)
+            + self.num_unflushed;
+        self.num_unflushed = 0;
+
+        if num_expanded > iteration.max_num_steps {
+            return Err(LibError::MaxNumStepsReached(iteration.max_num_steps));
+        }
+
+        Ok(!iteration.stop.load(Ordering::Relaxed)
+            && self.subtree_idx <= iteration.solution_subtree.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::ops::ControlFlow;
+
+    use super::*;
+    use crate::solver::{ida_star, progress::Progress, test::examples::shuffled_fields};
+
+    #[test]
+    fn test_find_swap_order_zero_moves() -> Result<(), LibError> {
+        let fields = vec![0, 1, 2, 3];
+        let swap_order = find_swap_order(&fields, 2, 2, 10)?;
+        assert_eq!(swap_order, Vec::with_capacity(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_swap_order_four_swaps() -> Result<(), LibError> {
+        let fields = vec![8, 1, 2, 0, 3, 5, 6, 4, 7];
+        let swap_order = find_swap_order(&fields, 3, 3, 256)?;
+        assert_eq!(swap_order, vec![(0, 3), (3, 4), (4, 7), (7, 8)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_matches_single_threaded() -> Result<(), LibError> {
+        let fields = vec![8, 5, 2, 1, 0, 7, 6, 4, 3];
+        let heuristic = ManhattanDistance::new(3, 3);
+        let expected = ida_star::find_swap_order(&fields, 3, 3, 1_000_000)?;
+
+        for num_threads in [1, 2, 4, 7] {
+            let outcome = find_swap_order_with_progress(
+                &fields,
+                3,
+                3,
+                1_000_000,
+                num_threads,
+                &heuristic,
+                &mut NoProgress,
+            )?;
+            assert_eq!(outcome.swaps, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_num_steps_reached() {
+        let fields = vec![8, 5, 2, 1, 0, 7, 6, 4, 3];
+        assert!(matches!(
+            find_swap_order(&fields, 3, 3, 2),
+            Err(LibError::MaxNumStepsReached(2))
+        ));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let fields = shuffled_fields(5, 5, 1000, 0);
+        let mut observer = |_: &Progress| ControlFlow::Break(());
+        let result = find_swap_order_with_progress(
+            &fields,
+            5,
+            5,
+            usize::MAX,
+            2,
+            &ManhattanDistance::new(5, 5),
+            &mut observer,
+        );
+        assert!(matches!(result, Err(LibError::Cancelled)));
+    }
+}
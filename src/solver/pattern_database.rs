@@ -0,0 +1,499 @@
+//! Additive disjoint pattern databases
+//!
+//! A pattern is a subset of the fields of a puzzle. Its database stores for
+//! every placement of the pattern fields the number of swaps that move one of
+//! the pattern fields, which are at least required to bring them to their goal
+//! positions. Swaps with fields outside of the pattern are not counted. If the
+//! patterns are disjoint, every swap is counted by at most one pattern and the
+//! sum over all pattern databases is an admissible heuristic.
+//!
+//! The databases are built by a retrograde breadth-first-search from the goal
+//! state and can be stored in a versioned and checksummed binary format:
+//!
+//! ```conf
+//! magic        4 bytes  "SPDB"
+//! version      u16
+//! width        u8
+//! height       u8
+//! num patterns u8
+//! per pattern:
+//!   num fields   u8
+//!   fields       [u8; num fields]
+//!   num entries  u32
+//!   distances    [u8; num entries]
+//! checksum     u64 (FNV-1a of all preceding bytes)
+//! ```
+//!
+//! All integers are stored in little endian byte order.
+//!
+
+use std::{collections::VecDeque, hash::Hasher};
+
+use fnv::FnvHasher;
+
+use crate::{board::get_swappable_neighbours, error::LibError, solver::heuristic::Heuristic};
+
+const MAGIC: &[u8; 4] = b"SPDB";
+const FORMAT_VERSION: u16 = 1;
+const UNSEEN: u8 = u8::MAX;
+
+/// Lookup structure for a set of disjoint patterns.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatternDatabase {
+    width: usize,
+    height: usize,
+    patterns: Vec<Pattern>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Pattern {
+    /// Fields (IDs) which are part of the pattern.
+    fields: Vec<u8>,
+    /// Minimum number of swaps for every placement of the pattern fields.
+    ///
+    /// The index is the rank of the positions of the pattern fields, see
+    /// [`rank_positions`].
+    distances: Vec<u8>,
+}
+
+impl PatternDatabase {
+    /// Build the databases for a partition of the fields of a board.
+    ///
+    /// The patterns have to be disjoint and may not contain the empty field.
+    /// Building the database of a pattern with `k` fields on a board with `n`
+    /// positions requires `n! / (n - k - 1)!` bytes of memory, so e.g. a 6-6-3
+    /// partition of a 4x4 board is feasible while a 7-8 partition is not.
+    pub fn build(width: usize, height: usize, partition: &[&[u8]]) -> Result<Self, LibError> {
+        validate_partition(width, height, partition)?;
+
+        let patterns = partition
+            .iter()
+            .map(|fields| {
+                log::debug!("Building pattern database for fields {:?}", fields);
+                Pattern {
+                    fields: fields.to_vec(),
+                    distances: build_distances(width, height, fields),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            width,
+            height,
+            patterns,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Check whether the database was built for boards of the given size.
+    pub fn matches(&self, width: usize, height: usize) -> bool {
+        (self.width, self.height) == (width, height)
+    }
+
+    /// Serialize the databases to the binary format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.push(self.width as u8);
+        bytes.push(self.height as u8);
+        bytes.push(self.patterns.len() as u8);
+
+        for pattern in self.patterns.iter() {
+            bytes.push(pattern.fields.len() as u8);
+            bytes.extend_from_slice(&pattern.fields);
+            bytes.extend_from_slice(&(pattern.distances.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&pattern.distances);
+        }
+
+        let checksum = checksum(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+
+        bytes
+    }
+
+    /// Deserialize databases from the binary format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LibError> {
+        if bytes.len() < MAGIC.len() + 8 {
+            return Err(LibError::InvalidPatternDatabase("too short"));
+        }
+
+        let (payload, checksum_bytes) = bytes.split_at(bytes.len() - 8);
+        let mut reader = Reader { bytes: payload };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(LibError::InvalidPatternDatabase("missing magic bytes"));
+        }
+
+        let version = u16::from_le_bytes(reader.take_array()?);
+        if version != FORMAT_VERSION {
+            return Err(LibError::UnsupportedPatternDatabaseVersion(version));
+        }
+
+        let expected_checksum = u64::from_le_bytes(checksum_bytes.try_into().unwrap());
+        let actual_checksum = checksum(payload);
+        if expected_checksum != actual_checksum {
+            return Err(LibError::ChecksumMismatch {
+                expected: expected_checksum,
+                actual: actual_checksum,
+            });
+        }
+
+        let [width, height, num_patterns] = reader.take_array()?;
+        let (width, height) = (width as usize, height as usize);
+        validate_board_size(width, height)?;
+
+        let mut patterns = Vec::with_capacity(num_patterns as usize);
+        for _ in 0..num_patterns {
+            let [num_fields] = reader.take_array()?;
+            let fields = reader.take(num_fields as usize)?.to_vec();
+            if fields.len() >= width * height {
+                return Err(LibError::InvalidPatternDatabase("too many pattern fields"));
+            }
+            let num_entries = u32::from_le_bytes(reader.take_array()?) as usize;
+            if num_entries != num_placements(width * height, fields.len()) {
+                return Err(LibError::InvalidPatternDatabase(
+                    "unexpected number of entries",
+                ));
+            }
+            let distances = reader.take(num_entries)?.to_vec();
+
+            patterns.push(Pattern { fields, distances });
+        }
+
+        if !reader.bytes.is_empty() {
+            return Err(LibError::InvalidPatternDatabase("trailing bytes"));
+        }
+
+        let partition: Vec<&[u8]> = patterns.iter().map(|p| p.fields.as_slice()).collect();
+        validate_partition(width, height, &partition)?;
+
+        Ok(Self {
+            width,
+            height,
+            patterns,
+        })
+    }
+}
+
+/// Heuristic of a solver with an optional pattern database, see
+/// [`select_heuristic`].
+#[derive(Clone, Debug)]
+pub enum SelectedHeuristic<'a, H> {
+    Database(&'a PatternDatabase),
+    Fallback(H),
+}
+
+/// Select the pattern database if it matches the board size and the fallback
+/// heuristic otherwise.
+///
+/// A database of another board size cannot estimate the fields of the board,
+/// so solvers silently fall back to a heuristic without a database.
+pub fn select_heuristic<H: Heuristic>(
+    pattern_database: Option<&PatternDatabase>,
+    width: usize,
+    height: usize,
+    fallback: H,
+) -> SelectedHeuristic<'_, H> {
+    match pattern_database {
+        Some(database) if database.matches(width, height) => SelectedHeuristic::Database(database),
+        _ => SelectedHeuristic::Fallback(fallback),
+    }
+}
+
+impl<H: Heuristic> Heuristic for SelectedHeuristic<'_, H> {
+    fn estimate(&self, fields: &[u8]) -> usize {
+        match self {
+            SelectedHeuristic::Database(database) => database.estimate(fields),
+            SelectedHeuristic::Fallback(heuristic) => heuristic.estimate(fields),
+        }
+    }
+}
+
+impl Heuristic for PatternDatabase {
+    fn estimate(&self, fields: &[u8]) -> usize {
+        // Map field IDs to their positions on the board
+        let mut id2idx = vec![0; fields.len()];
+        for (idx, &value) in fields.iter().enumerate() {
+            id2idx[value as usize] = idx;
+        }
+
+        let mut positions = Vec::new();
+        self.patterns
+            .iter()
+            .map(|pattern| {
+                positions.clear();
+                positions.extend(pattern.fields.iter().map(|&id| id2idx[id as usize]));
+                pattern.distances[rank_positions(&positions, fields.len())] as usize
+            })
+            .sum()
+    }
+}
+
+/// Check that the patterns are disjoint and only contain valid non-empty fields.
+fn validate_partition(width: usize, height: usize, partition: &[&[u8]]) -> Result<(), LibError> {
+    validate_board_size(width, height)?;
+
+    let num_fields = width * height;
+    let mut seen = vec![false; num_fields];
+    for &field in partition.iter().flat_map(|fields| fields.iter()) {
+        if field as usize >= num_fields - 1 {
+            return Err(LibError::InvalidPatternField(field));
+        }
+        if seen[field as usize] {
+            return Err(LibError::DuplicatePatternField(field));
+        }
+        seen[field as usize] = true;
+    }
+
+    Ok(())
+}
+
+/// Check that the fields of a board can be represented by `u8` IDs.
+fn validate_board_size(width: usize, height: usize) -> Result<(), LibError> {
+    match width * height {
+        0..=1 => Err(LibError::InvalidPatternDatabase("board too small")),
+        2..=255 => Ok(()),
+        _ => Err(LibError::InvalidPatternDatabase("board too large")),
+    }
+}
+
+/// Compute the distances of all placements of the pattern fields.
+///
+/// We run a 0-1 breadth-first-search from the goal state over the abstract
+/// states consisting of the positions of the pattern fields and the empty
+/// field. Moving the empty field to a position of a field outside of the
+/// pattern costs nothing, swapping it with a pattern field costs one. The
+/// distance of a placement is the minimum over all positions of the empty
+/// field.
+fn build_distances(width: usize, height: usize, pattern: &[u8]) -> Vec<u8> {
+    let num_fields = width * height;
+
+    // Positions of the pattern fields followed by the empty field
+    let mut positions: Vec<usize> = pattern.iter().map(|&id| id as usize).collect();
+    positions.push(num_fields - 1);
+
+    let mut state_distances = vec![UNSEEN; num_placements(num_fields, positions.len())];
+    let initial_rank = rank_positions(&positions, num_fields);
+    state_distances[initial_rank] = 0;
+    let mut to_explore = VecDeque::from([initial_rank]);
+
+    let mut occupant = vec![None; num_fields];
+    while let Some(rank) = to_explore.pop_front() {
+        let distance = state_distances[rank];
+        unrank_positions(rank, num_fields, &mut positions);
+
+        occupant.fill(None);
+        for (i, &idx) in positions.iter().enumerate() {
+            occupant[idx] = Some(i);
+        }
+
+        let empty_field_pos = positions.len() - 1;
+        let empty_field_idx = positions[empty_field_pos];
+        for neighbour_idx in get_swappable_neighbours(width, height, empty_field_idx) {
+            let mut next_positions = positions.clone();
+            next_positions[empty_field_pos] = neighbour_idx;
+
+            let cost = match occupant[neighbour_idx] {
+                Some(i) => {
+                    next_positions[i] = empty_field_idx;
+                    1
+                }
+                None => 0,
+            };
+
+            let next_rank = rank_positions(&next_positions, num_fields);
+            let next_distance = distance + cost;
+            if next_distance < state_distances[next_rank] {
+                state_distances[next_rank] = next_distance;
+                match cost {
+                    0 => to_explore.push_front(next_rank),
+                    _ => to_explore.push_back(next_rank),
+                }
+            }
+        }
+    }
+
+    // Minimize over the empty field positions, which are the last digit of the
+    // rank of the abstract state.
+    let num_empty_field_positions = num_fields - pattern.len();
+    state_distances
+        .chunks(num_empty_field_positions)
+        .map(|chunk| chunk.iter().copied().min().unwrap_or(UNSEEN))
+        .collect()
+}
+
+/// Number of placements of `k` distinct fields on `n` positions.
+fn num_placements(n: usize, k: usize) -> usize {
+    ((n - k + 1)..=n).product()
+}
+
+/// Compute the rank of distinct positions among all placements.
+///
+/// Every position is counted among the positions not used by earlier entries,
+/// which gives a number in a mixed radix system with the bases `n, n - 1, ...`.
+fn rank_positions(positions: &[usize], n: usize) -> usize {
+    positions.iter().enumerate().fold(0, |rank, (i, &pos)| {
+        let num_smaller_before = positions[..i].iter().filter(|&&p| p < pos).count();
+        rank * (n - i) + pos - num_smaller_before
+    })
+}
+
+/// Inverse of [`rank_positions`] for `positions.len()` positions.
+fn unrank_positions(mut rank: usize, n: usize, positions: &mut [usize]) {
+    let k = positions.len();
+
+    // Extract the mixed radix digits from the last to the first
+    for i in (0..k).rev() {
+        positions[i] = rank % (n - i);
+        rank /= n - i;
+    }
+
+    // Translate digits to positions by skipping positions used before
+    let mut used = vec![false; n];
+    for pos in positions.iter_mut() {
+        let idx = (0..n)
+            .filter(|&idx| !used[idx])
+            .nth(*pos)
+            .expect("digit is smaller than the number of unused positions");
+        used[idx] = true;
+        *pos = idx;
+    }
+}
+
+/// FNV-1a checksum of the binary formats.
+pub(crate) fn checksum(bytes: &[u8]) -> u64 {
+    let mut hasher = FnvHasher::default();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], LibError> {
+        if self.bytes.len() < len {
+            return Err(LibError::InvalidPatternDatabase("unexpected end of data"));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], LibError> {
+        self.take(N).map(|bytes| bytes.try_into().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::board::initialize_fields;
+
+    #[test]
+    fn test_rank_unrank_roundtrip() {
+        let n = 6;
+        let mut positions = vec![0; 3];
+        for rank in 0..num_placements(n, 3) {
+            unrank_positions(rank, n, &mut positions);
+            assert_eq!(rank_positions(&positions, n), rank);
+        }
+    }
+
+    #[test]
+    fn test_zero_on_goal() -> Result<(), LibError> {
+        let database = PatternDatabase::build(3, 3, &[&[0, 1, 2, 3], &[4, 5, 6, 7]])?;
+        assert_eq!(database.estimate(&initialize_fields(9).unwrap()), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_single_swap() -> Result<(), LibError> {
+        let database = PatternDatabase::build(3, 3, &[&[0, 1, 2, 3], &[4, 5, 6, 7]])?;
+        assert_eq!(database.estimate(&[0, 1, 2, 3, 4, 5, 6, 8, 7]), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_heuristic() -> Result<(), LibError> {
+        use crate::solver::heuristic::ManhattanDistance;
+
+        let database = PatternDatabase::build(3, 3, &[&[0, 1, 2, 3], &[4, 5, 6, 7]])?;
+        assert!(database.matches(3, 3));
+        assert!(!database.matches(3, 4));
+
+        let fallback = ManhattanDistance::new(4, 4);
+        assert!(matches!(
+            select_heuristic(Some(&database), 3, 3, fallback.clone()),
+            SelectedHeuristic::Database(_)
+        ));
+        assert!(matches!(
+            select_heuristic(Some(&database), 4, 4, fallback.clone()),
+            SelectedHeuristic::Fallback(_)
+        ));
+        assert!(matches!(
+            select_heuristic(None, 4, 4, fallback),
+            SelectedHeuristic::Fallback(_)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() -> Result<(), LibError> {
+        let database = PatternDatabase::build(3, 3, &[&[0, 1, 2], &[3, 4, 5], &[6, 7]])?;
+        let bytes = database.to_bytes();
+        assert_eq!(PatternDatabase::from_bytes(&bytes)?, database);
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_corruption() -> Result<(), LibError> {
+        let database = PatternDatabase::build(3, 3, &[&[0, 1, 2], &[3, 4, 5], &[6, 7]])?;
+        let mut bytes = database.to_bytes();
+        bytes[20] ^= 1;
+        assert!(matches!(
+            PatternDatabase::from_bytes(&bytes),
+            Err(LibError::ChecksumMismatch { .. })
+        ));
+
+        let mut bytes = database.to_bytes();
+        bytes[4] = 2;
+        assert!(matches!(
+            PatternDatabase::from_bytes(&bytes),
+            Err(LibError::UnsupportedPatternDatabaseVersion(2))
+        ));
+
+        let bytes = database.to_bytes();
+        assert!(matches!(
+            PatternDatabase::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(LibError::ChecksumMismatch { .. })
+        ));
+        assert!(matches!(
+            PatternDatabase::from_bytes(&bytes[1..]),
+            Err(LibError::InvalidPatternDatabase(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_partition() {
+        assert!(matches!(
+            PatternDatabase::build(3, 3, &[&[0, 1], &[1, 2]]),
+            Err(LibError::DuplicatePatternField(1))
+        ));
+        assert!(matches!(
+            PatternDatabase::build(3, 3, &[&[0, 8]]),
+            Err(LibError::InvalidPatternField(8))
+        ));
+    }
+}
@@ -0,0 +1,315 @@
+//! Peephole optimizer for swap sequences
+//!
+//! Solvers which are not optimal, like the divide and conquer solver, produce
+//! swap sequences with redundant parts. The optimizer improves any valid swap
+//! sequence locally:
+//!
+//! - Two consecutive swaps which undo each other are removed, as well as
+//!   swaps which do not move the empty field at all.
+//! - Every short window of consecutive swaps is replaced by a shortest swap
+//!   sequence between the states before and after the window if that is
+//!   shorter.
+//!
+//! Both steps are repeated until the sequence does not get shorter anymore.
+//! The result leads from the same initial state to the same final state.
+//!
+
+use crate::{
+    board::{get_empty_field_idx, get_row_col_from_idx, get_swappable_neighbours},
+    error::LibError,
+    solver::verify::check_swap,
+};
+
+/// Window size which keeps the optimizer fast even for large boards.
+pub const DEFAULT_WINDOW_SIZE: usize = 10;
+
+/// Optimized swap sequence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeepholeOutcome {
+    /// Swaps leading from the initial to the same final state as before.
+    pub swaps: Vec<(usize, usize)>,
+    /// Number of swaps which were removed.
+    pub num_saved: usize,
+}
+
+/// Optimize a swap sequence starting from `fields`.
+///
+/// Windows of up to `window_size` swaps are re-solved optimally. Larger windows
+/// find more improvements but take exponentially longer.
+pub fn optimize(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    swaps: &[(usize, usize)],
+    window_size: usize,
+) -> Result<PeepholeOutcome, LibError> {
+    validate_swaps(fields, width, height, swaps)?;
+
+    let mut optimized = cancel_inverse_pairs(swaps);
+    loop {
+        let num_swaps = optimized.len();
+        optimized = shorten_windows(fields, width, height, optimized, window_size);
+        optimized = cancel_inverse_pairs(&optimized);
+
+        if optimized.len() == num_swaps {
+            break;
+        }
+    }
+
+    log::debug!(
+        "Peephole optimizer reduced {} to {} swaps",
+        swaps.len(),
+        optimized.len()
+    );
+
+    Ok(PeepholeOutcome {
+        num_saved: swaps.len() - optimized.len(),
+        swaps: optimized,
+    })
+}
+
+/// Remove all pairs of consecutive swaps which undo each other and swaps of
+/// the empty field with itself.
+///
+/// Removing a pair can make its neighbours consecutive, which are removed as
+/// well if they undo each other.
+pub fn cancel_inverse_pairs(swaps: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut remaining: Vec<(usize, usize)> = Vec::with_capacity(swaps.len());
+    for &(empty_idx_before, empty_idx_after) in swaps {
+        if empty_idx_before == empty_idx_after {
+            continue;
+        }
+
+        if remaining.last() == Some(&(empty_idx_after, empty_idx_before)) {
+            remaining.pop();
+        } else {
+            remaining.push((empty_idx_before, empty_idx_after));
+        }
+    }
+    remaining
+}
+
+/// Check that every swap moves the empty field to a neighbour or keeps it in
+/// place.
+fn validate_swaps(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    swaps: &[(usize, usize)],
+) -> Result<(), LibError> {
+    let mut empty_field_idx = get_empty_field_idx(fields)?;
+    for (step, &swap) in swaps.iter().enumerate() {
+        if swap != (empty_field_idx, empty_field_idx) {
+            check_swap(empty_field_idx, swap, width, height)
+                .map_err(|reason| LibError::InvalidSwap { step, swap, reason })?;
+        }
+        empty_field_idx = swap.1;
+    }
+    Ok(())
+}
+
+/// Replace windows of swaps by shorter ones in a single pass.
+fn shorten_windows(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    mut swaps: Vec<(usize, usize)>,
+    window_size: usize,
+) -> Vec<(usize, usize)> {
+    // State before the window starting at `window_start`
+    let mut state = fields.to_owned();
+    let mut window_start = 0;
+
+    while window_start + 2 <= swaps.len() {
+        let window_end = usize::min(window_start + window_size, swaps.len());
+        let window = window_start..window_end;
+
+        let mut end_state = state.clone();
+        for &(empty_idx_before, empty_idx_after) in swaps[window.clone()].iter() {
+            end_state.swap(empty_idx_before, empty_idx_after);
+        }
+
+        // Every path between two states has the same parity, so a shorter
+        // path is at least two swaps shorter.
+        let max_num_swaps = window.len() - 2;
+        let empty_field_idx = swaps[window_start].0;
+        let mut search = WindowSearch::new(&state, &end_state, width, height);
+        match search.find(empty_field_idx, max_num_swaps) {
+            Some(shorter) => {
+                // Stay at the same start, the window now reaches further
+                swaps.splice(window, shorter);
+            }
+            None => {
+                let (empty_idx_before, empty_idx_after) = swaps[window_start];
+                state.swap(empty_idx_before, empty_idx_after);
+                window_start += 1;
+            }
+        }
+    }
+
+    swaps
+}
+
+/// Result of a bounded depth-first search.
+enum Bounded {
+    Found,
+    Exceeded(usize),
+}
+
+/// IDA* search between two arbitrary states.
+///
+/// The Manhattan distance to the goal state is updated incrementally with
+/// every swap, which keeps the search cheap on large boards.
+struct WindowSearch {
+    fields: Vec<u8>,
+    /// Goal index of every field value.
+    goal_positions: Vec<usize>,
+    width: usize,
+    height: usize,
+    swaps: Vec<(usize, usize)>,
+}
+
+impl WindowSearch {
+    fn new(fields: &[u8], goal: &[u8], width: usize, height: usize) -> Self {
+        let mut goal_positions = vec![0; goal.len()];
+        for (idx, &value) in goal.iter().enumerate() {
+            goal_positions[value as usize] = idx;
+        }
+
+        Self {
+            fields: fields.to_owned(),
+            goal_positions,
+            width,
+            height,
+            swaps: Vec::new(),
+        }
+    }
+
+    /// Find a shortest swap sequence to the goal with at most
+    /// `max_num_swaps` swaps.
+    fn find(
+        &mut self,
+        empty_field_idx: usize,
+        max_num_swaps: usize,
+    ) -> Option<Vec<(usize, usize)>> {
+        let empty_field_val = self.fields.len() - 1;
+        let estimate: usize = self
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, &value)| value as usize != empty_field_val)
+            .map(|(idx, &value)| self.distance(idx, self.goal_positions[value as usize]))
+            .sum();
+
+        let mut bound = estimate;
+        while bound <= max_num_swaps {
+            match self.search(empty_field_idx, None, estimate, bound) {
+                Bounded::Found => return Some(std::mem::take(&mut self.swaps)),
+                Bounded::Exceeded(next_bound) => bound = next_bound,
+            }
+        }
+        None
+    }
+
+    fn search(
+        &mut self,
+        empty_field_idx: usize,
+        prev_empty_field_idx: Option<usize>,
+        estimate: usize,
+        bound: usize,
+    ) -> Bounded {
+        let total_estimate = self.swaps.len() + estimate;
+        if total_estimate > bound {
+            return Bounded::Exceeded(total_estimate);
+        }
+
+        // If all other fields are in place, the empty field is as well
+        if estimate == 0 {
+            return Bounded::Found;
+        }
+
+        let mut next_bound = usize::MAX;
+        for neighbour_idx in get_swappable_neighbours(self.width, self.height, empty_field_idx) {
+            if Some(neighbour_idx) == prev_empty_field_idx {
+                continue;
+            }
+
+            // The neighbour field moves to the position of the empty field
+            let goal_idx = self.goal_positions[self.fields[neighbour_idx] as usize];
+            let next_estimate = estimate + self.distance(empty_field_idx, goal_idx)
+                - self.distance(neighbour_idx, goal_idx);
+
+            self.fields.swap(empty_field_idx, neighbour_idx);
+            self.swaps.push((empty_field_idx, neighbour_idx));
+
+            match self.search(neighbour_idx, Some(empty_field_idx), next_estimate, bound) {
+                Bounded::Found => return Bounded::Found,
+                Bounded::Exceeded(total_estimate) => next_bound = next_bound.min(total_estimate),
+            }
+
+            self.swaps.pop();
+            self.fields.swap(empty_field_idx, neighbour_idx);
+        }
+
+        Bounded::Exceeded(next_bound)
+    }
+
+    fn distance(&self, idx: usize, goal_idx: usize) -> usize {
+        let (row, col) = get_row_col_from_idx(idx, self.width);
+        let (goal_row, goal_col) = get_row_col_from_idx(goal_idx, self.width);
+        row.abs_diff(goal_row) + col.abs_diff(goal_col)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::InvalidSwapReason;
+
+    #[test]
+    fn test_cancel_inverse_pairs() {
+        let swaps = [(8, 7), (7, 4), (4, 4), (4, 7), (7, 8), (8, 5)];
+        assert_eq!(cancel_inverse_pairs(&swaps), vec![(8, 5)]);
+    }
+
+    #[test]
+    fn test_shorten_detour() -> Result<(), LibError> {
+        // Moving the empty field around a 2x2 square twice is the same as
+        // moving it around once in the other direction
+        let fields = [0, 1, 2, 3, 4, 5, 6, 7, 8];
+        let cycle = [(8, 7), (7, 4), (4, 5), (5, 8)];
+        let swaps = cycle.repeat(2);
+
+        let outcome = optimize(&fields, 3, 3, &swaps, DEFAULT_WINDOW_SIZE)?;
+        assert_eq!(outcome.swaps, vec![(8, 5), (5, 4), (4, 7), (7, 8)]);
+        assert_eq!(outcome.num_saved, 4);
+
+        let mut expected = fields;
+        let mut actual = fields;
+        for (empty_idx_before, empty_idx_after) in swaps {
+            expected.swap(empty_idx_before, empty_idx_after);
+        }
+        for (empty_idx_before, empty_idx_after) in outcome.swaps {
+            actual.swap(empty_idx_before, empty_idx_after);
+        }
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_swap() {
+        let fields = [0, 1, 2, 3];
+        assert!(matches!(
+            optimize(&fields, 2, 2, &[(3, 2), (3, 1)], DEFAULT_WINDOW_SIZE),
+            Err(LibError::InvalidSwap {
+                step: 1,
+                swap: (3, 1),
+                reason: InvalidSwapReason::NotEmptyField { empty_field_idx: 2 },
+            })
+        ));
+        assert!(matches!(
+            optimize(&fields, 2, 2, &[(3, 0)], DEFAULT_WINDOW_SIZE),
+            Err(LibError::InvalidSwap { step: 0, .. })
+        ));
+    }
+}
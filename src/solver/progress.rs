@@ -0,0 +1,189 @@
+//! Progress reporting and cancellation of solvers
+//!
+//! Solvers report their [`Progress`] to a [`ProgressObserver`] at regular
+//! intervals. The observer decides whether the solver continues or stops with
+//! [`LibError::Cancelled`], e.g. because a user pressed a button or a time
+//! budget ran out.
+//!
+
+use std::{ops::ControlFlow, time::Duration};
+
+use crate::error::LibError;
+
+/// Number of expanded states between two reports of the searching solvers.
+pub(crate) const REPORT_INTERVAL: usize = 10_000;
+
+/// Snapshot of the progress of a solver.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// Number of expanded states so far. The divide and conquer solver
+    /// reports the number of solved fields instead.
+    pub num_expanded: usize,
+    /// Current depth of the search, e.g. the level of a breadth-first-search
+    /// or the bound of IDA*. The divide and conquer solver reports the number
+    /// of swaps so far instead.
+    pub depth: usize,
+    /// Time since the solver started.
+    pub elapsed: Duration,
+}
+
+/// Receiver of progress reports, which may cancel the solver.
+pub trait ProgressObserver {
+    /// Handle a progress report, returning `ControlFlow::Break` cancels the
+    /// solver.
+    fn on_progress(&mut self, progress: &Progress) -> ControlFlow<()>;
+}
+
+impl<F: FnMut(&Progress) -> ControlFlow<()>> ProgressObserver for F {
+    fn on_progress(&mut self, progress: &Progress) -> ControlFlow<()> {
+        self(progress)
+    }
+}
+
+/// Observer which ignores all reports and never cancels.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoProgress;
+
+impl ProgressObserver for NoProgress {
+    fn on_progress(&mut self, _progress: &Progress) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+/// Observer which cancels the solver once a wall-clock budget is used up.
+///
+/// The budget is only checked when the solver reports, so the solver may run a
+/// little longer.
+#[derive(Clone, Copy, Debug)]
+pub struct TimeLimit(pub Duration);
+
+impl ProgressObserver for TimeLimit {
+    fn on_progress(&mut self, progress: &Progress) -> ControlFlow<()> {
+        match progress.elapsed > self.0 {
+            true => ControlFlow::Break(()),
+            false => ControlFlow::Continue(()),
+        }
+    }
+}
+
+/// Helper for solvers to report their progress at intervals.
+pub(crate) struct ProgressReporter<'a> {
+    observer: &'a mut dyn ProgressObserver,
+    stopwatch: Stopwatch,
+    interval: usize,
+    next_report: usize,
+}
+
+impl<'a> ProgressReporter<'a> {
+    /// Create a reporter which reports every `interval` expanded states.
+    pub(crate) fn new(observer: &'a mut dyn ProgressObserver, interval: usize) -> Self {
+        Self {
+            observer,
+            stopwatch: Stopwatch::start(),
+            interval,
+            next_report: interval,
+        }
+    }
+
+    /// Report the progress if the next interval is reached.
+    pub(crate) fn update(&mut self, num_expanded: usize, depth: usize) -> Result<(), LibError> {
+        if num_expanded < self.next_report {
+            return Ok(());
+        }
+        self.next_report = num_expanded + self.interval;
+
+        let progress = Progress {
+            num_expanded,
+            depth,
+            elapsed: self.stopwatch.elapsed(),
+        };
+
+        match self.observer.on_progress(&progress) {
+            ControlFlow::Continue(()) => Ok(()),
+            ControlFlow::Break(()) => Err(LibError::Cancelled),
+        }
+    }
+}
+
+/// Clock measuring the elapsed time, which also works in the browser.
+///
+/// `std::time::Instant` panics on `wasm32-unknown-unknown`, so we use the
+/// JavaScript clock there.
+struct Stopwatch {
+    #[cfg(target_arch = "wasm32")]
+    start_millis: f64,
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,
+}
+
+impl Stopwatch {
+    #[cfg(target_arch = "wasm32")]
+    fn start() -> Self {
+        Self {
+            start_millis: js_sys::Date::now(),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn start() -> Self {
+        Self {
+            start: std::time::Instant::now(),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn elapsed(&self) -> Duration {
+        Duration::from_secs_f64((js_sys::Date::now() - self.start_millis).max(0.) / 1000.)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reports_at_intervals() {
+        let mut reports = Vec::new();
+        let mut observer = |progress: &Progress| {
+            reports.push(progress.num_expanded);
+            ControlFlow::Continue(())
+        };
+
+        let mut reporter = ProgressReporter::new(&mut observer, 10);
+        for num_expanded in 0..35 {
+            reporter.update(num_expanded, 0).unwrap();
+        }
+        assert_eq!(reports, vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut observer = |progress: &Progress| match progress.num_expanded >= 2 {
+            true => ControlFlow::Break(()),
+            false => ControlFlow::Continue(()),
+        };
+
+        let mut reporter = ProgressReporter::new(&mut observer, 1);
+        assert!(reporter.update(1, 0).is_ok());
+        assert!(matches!(reporter.update(2, 0), Err(LibError::Cancelled)));
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let mut progress = Progress {
+            num_expanded: 0,
+            depth: 0,
+            elapsed: Duration::from_millis(10),
+        };
+        let mut time_limit = TimeLimit(Duration::from_millis(20));
+        assert_eq!(time_limit.on_progress(&progress), ControlFlow::Continue(()));
+
+        progress.elapsed = Duration::from_millis(30);
+        assert_eq!(time_limit.on_progress(&progress), ControlFlow::Break(()));
+    }
+}
@@ -0,0 +1,335 @@
+//! Common interface of all solvers
+//!
+//! Every solver implements the [`Solver`] trait, which takes a [`Puzzle`] and
+//! returns a [`Solution`]. The [`SolverRegistry`] holds one instance of every
+//! solver and allows to pick them by name, e.g. from a button or a benchmark,
+//! or to list all solvers which support a given board size.
+//!
+
+use std::sync::Arc;
+
+use crate::{
+    error::LibError,
+    solver::{
+        beam_search::{BeamSearch, DEFAULT_BEAM_WIDTH},
+        divide_and_conquer::DivideAndConquer,
+        ida_star::IdaStar,
+        lookup_table::LookupTableSolver,
+        move_metric::{count_moves, to_slides, MoveMetric, Slide},
+        optimal::{BidirectionalSearch, BreadthFirstSearch},
+        pattern_database::PatternDatabase,
+        progress::{NoProgress, ProgressObserver},
+        weighted_a_star::{WeightedAStar, DEFAULT_EPSILON},
+    },
+};
+
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+use crate::solver::parallel_ida_star::{default_num_threads, ParallelIdaStar};
+
+/// Puzzle state to solve.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Puzzle {
+    /// Field IDs in row-major order, the empty field has the largest ID.
+    pub fields: Vec<u8>,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Puzzle {
+    /// Create a puzzle and check that the fields match the board size.
+    pub fn new(fields: Vec<u8>, width: usize, height: usize) -> Result<Self, LibError> {
+        if fields.len() != width * height {
+            return Err(LibError::FieldsBoardMismatch {
+                len: fields.len(),
+                expected: (width * height) as i32,
+            });
+        }
+
+        Ok(Self {
+            fields,
+            width,
+            height,
+        })
+    }
+}
+
+/// Solution of a puzzle.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Solution {
+    /// Swaps as tuples of the empty field index before and after the swap.
+    pub swaps: Vec<(usize, usize)>,
+}
+
+impl Solution {
+    /// Merge the swaps into slides of the multi-tile metric, e.g. to animate
+    /// each slide as one move.
+    pub fn slides(&self) -> Vec<Slide> {
+        to_slides(&self.swaps)
+    }
+
+    /// Number of moves in a metric.
+    pub fn num_moves(&self, metric: MoveMetric) -> usize {
+        count_moves(&self.swaps, metric)
+    }
+}
+
+/// A puzzle solver.
+pub trait Solver {
+    /// Name and capabilities of the solver.
+    fn info(&self) -> SolverInfo;
+
+    /// Find swaps which bring the puzzle into its solved state.
+    fn solve(&self, puzzle: &Puzzle) -> Result<Solution, LibError> {
+        self.solve_with_progress(puzzle, &mut NoProgress)
+    }
+
+    /// Find swaps which bring the puzzle into its solved state and report the
+    /// progress to an observer, which may cancel the solver.
+    fn solve_with_progress(
+        &self,
+        puzzle: &Puzzle,
+        observer: &mut dyn ProgressObserver,
+    ) -> Result<Solution, LibError>;
+}
+
+/// Name and capabilities of a solver.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SolverInfo {
+    /// Unique name to pick the solver by.
+    pub name: &'static str,
+    /// Whether the solutions are shortest ones.
+    pub optimal: bool,
+    /// Metric in which the solutions are counted, and shortest if `optimal`.
+    pub metric: MoveMetric,
+    /// Whether the solver only supports boards with equal width and height.
+    pub square_only: bool,
+    /// Smallest supported width and height.
+    pub min_size: usize,
+    /// Largest number of fields the solver is practical for.
+    pub max_num_fields: usize,
+}
+
+impl SolverInfo {
+    /// Whether the solver supports boards of the given size.
+    pub fn supports(&self, width: usize, height: usize) -> bool {
+        (!self.square_only || width == height)
+            && width >= self.min_size
+            && height >= self.min_size
+            && width * height <= self.max_num_fields
+    }
+}
+
+/// Settings shared by the solvers of a registry.
+#[derive(Clone, Debug)]
+pub struct SolverConfig {
+    /// Limit of expanded states for the searching solvers.
+    pub max_num_steps: usize,
+    /// Pattern database used by IDA* and weighted A* if it matches the board
+    /// size.
+    pub pattern_database: Option<Arc<PatternDatabase>>,
+    /// Allowed suboptimality of weighted A*, the solutions are at most
+    /// `1 + epsilon` times as long as shortest ones.
+    pub epsilon: f64,
+    /// Number of states the beam search keeps per level.
+    pub beam_width: usize,
+    /// Metric minimized by the optimal searches which support it, i.e.
+    /// breadth-first search, bidirectional search and IDA*.
+    pub move_metric: MoveMetric,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            max_num_steps: 10_000_000,
+            pattern_database: None,
+            epsilon: DEFAULT_EPSILON,
+            beam_width: DEFAULT_BEAM_WIDTH,
+            move_metric: MoveMetric::SingleTile,
+        }
+    }
+}
+
+/// Collection of solvers which can be looked up by name.
+pub struct SolverRegistry {
+    solvers: Vec<Box<dyn Solver>>,
+}
+
+impl SolverRegistry {
+    /// Create a registry with all solvers of this crate.
+    pub fn new(config: SolverConfig) -> Self {
+        Self {
+            solvers: vec![
+                Box::new(LookupTableSolver),
+                Box::new(BreadthFirstSearch {
+                    max_num_steps: config.max_num_steps,
+                    move_metric: config.move_metric,
+                }),
+                Box::new(BidirectionalSearch {
+                    max_num_steps: config.max_num_steps,
+                    move_metric: config.move_metric,
+                }),
+                Box::new(IdaStar {
+                    max_num_steps: config.max_num_steps,
+                    pattern_database: config.pattern_database.clone(),
+                    move_metric: config.move_metric,
+                }),
+                #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+                Box::new(ParallelIdaStar {
+                    max_num_steps: config.max_num_steps,
+                    num_threads: default_num_threads(),
+                    pattern_database: config.pattern_database.clone(),
+                }),
+                Box::new(WeightedAStar {
+                    epsilon: config.epsilon,
+                    max_num_steps: config.max_num_steps,
+                    pattern_database: config.pattern_database,
+                }),
+                Box::new(BeamSearch {
+                    beam_width: config.beam_width,
+                }),
+                Box::new(DivideAndConquer),
+            ],
+        }
+    }
+
+    /// Add a solver, replacing a solver with the same name.
+    pub fn register(&mut self, solver: Box<dyn Solver>) {
+        let name = solver.info().name;
+        self.solvers.retain(|s| s.info().name != name);
+        self.solvers.push(solver);
+    }
+
+    /// Get a solver by its name.
+    pub fn get(&self, name: &str) -> Option<&dyn Solver> {
+        self.solvers
+            .iter()
+            .find(|solver| solver.info().name == name)
+            .map(|solver| solver.as_ref())
+    }
+
+    /// Iterate over all solvers.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Solver> {
+        self.solvers.iter().map(|solver| solver.as_ref())
+    }
+
+    /// Iterate over all solvers supporting boards of the given size.
+    pub fn supporting(&self, width: usize, height: usize) -> impl Iterator<Item = &dyn Solver> {
+        self.iter()
+            .filter(move |solver| solver.info().supports(width, height))
+    }
+}
+
+impl Default for SolverRegistry {
+    fn default() -> Self {
+        Self::new(SolverConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solver::verify::verify_solution;
+
+    #[test]
+    fn test_names_unique() {
+        let registry = SolverRegistry::default();
+        let mut names: Vec<_> = registry.iter().map(|solver| solver.info().name).collect();
+        let num_solvers = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), num_solvers);
+    }
+
+    #[test]
+    fn test_get() {
+        let registry = SolverRegistry::default();
+        for name in [
+            "lookup-table",
+            "bfs",
+            "bidirectional",
+            "ida-star",
+            "weighted-a-star",
+            "beam-search",
+            "divide-and-conquer",
+            #[cfg(feature = "parallel")]
+            "parallel-ida-star",
+        ] {
+            assert_eq!(registry.get(name).map(|s| s.info().name), Some(name));
+        }
+        assert!(registry.get("unknown").is_none());
+    }
+
+    #[test]
+    fn test_register_replaces() {
+        let mut registry = SolverRegistry::default();
+        let num_solvers = registry.iter().count();
+        registry.register(Box::new(BreadthFirstSearch {
+            max_num_steps: 1,
+            move_metric: MoveMetric::SingleTile,
+        }));
+        assert_eq!(registry.iter().count(), num_solvers);
+    }
+
+    #[test]
+    fn test_supporting() {
+        let registry = SolverRegistry::default();
+        assert!(registry
+            .supporting(3, 3)
+            .any(|s| s.info().name == "divide-and-conquer"));
+        assert!(registry
+            .supporting(2, 3)
+            .any(|s| s.info().name == "divide-and-conquer"));
+        assert_eq!(registry.supporting(1, 3).count(), 0);
+        let num_parallel = usize::from(cfg!(feature = "parallel"));
+        assert_eq!(registry.supporting(2, 8).count(), 6 + num_parallel);
+        assert!(registry.supporting(10, 10).all(|s| !s.info().optimal));
+    }
+
+    #[test]
+    fn test_solve_3x3() -> Result<(), LibError> {
+        let puzzle = Puzzle::new(vec![8, 5, 2, 1, 0, 7, 6, 4, 3], 3, 3)?;
+        let registry = SolverRegistry::default();
+
+        for solver in registry.supporting(3, 3) {
+            let solution = solver.solve(&puzzle)?;
+            verify_solution(&puzzle.fields, 3, 3, &solution.swaps)?;
+
+            if solver.info().optimal {
+                assert_eq!(solution.swaps.len(), 16);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_strips() -> Result<(), LibError> {
+        use crate::solver::test::examples::shuffled_fields;
+
+        let registry = SolverRegistry::default();
+        for (width, height) in [(2, 4), (4, 2), (2, 5)] {
+            let puzzle = Puzzle::new(shuffled_fields(width, height, 100, 0), width, height)?;
+
+            let mut optimal_lengths = Vec::new();
+            for solver in registry.supporting(width, height) {
+                let solution = solver.solve(&puzzle)?;
+                verify_solution(&puzzle.fields, width, height, &solution.swaps)?;
+
+                if solver.info().optimal {
+                    optimal_lengths.push(solution.swaps.len());
+                }
+            }
+            let num_parallel = usize::from(cfg!(feature = "parallel"));
+            assert_eq!(optimal_lengths.len(), 3 + num_parallel);
+            assert!(optimal_lengths.windows(2).all(|pair| pair[0] == pair[1]));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_puzzle_mismatch() {
+        assert!(matches!(
+            Puzzle::new(vec![0, 1, 2], 2, 2),
+            Err(LibError::FieldsBoardMismatch { len: 3, .. })
+        ));
+    }
+}
@@ -0,0 +1,112 @@
+//! Exact, packed keys of puzzle states
+//!
+//! Solvers which remember visited states need a key per state. A hash of the
+//! fields may collide, so we pack the fields into a key which can be unpacked
+//! again. Small boards fit into a single integer, which is cheap to copy and
+//! to hash.
+//!
+
+use std::hash::Hash;
+
+/// Exact encoding of the fields of a puzzle state.
+pub(crate) trait StateKey: Clone + Eq + Hash {
+    /// Whether states with `num_fields` fields can be encoded.
+    fn fits(num_fields: usize) -> bool;
+
+    /// Encode the fields.
+    fn pack(fields: &[u8]) -> Self;
+
+    /// Decode the key into `fields`, which must have the packed length.
+    fn unpack_into(&self, fields: &mut [u8]);
+}
+
+/// Four bits per field for boards with up to 16 fields, e.g. 4x4.
+impl StateKey for u64 {
+    fn fits(num_fields: usize) -> bool {
+        num_fields <= 16
+    }
+
+    fn pack(fields: &[u8]) -> Self {
+        fields
+            .iter()
+            .enumerate()
+            .fold(0, |key, (idx, &value)| key | (value as u64) << (4 * idx))
+    }
+
+    fn unpack_into(&self, fields: &mut [u8]) {
+        for (idx, field) in fields.iter_mut().enumerate() {
+            *field = (self >> (4 * idx)) as u8 & 0xf;
+        }
+    }
+}
+
+/// Five bits per field for boards with up to 25 fields, e.g. 5x5.
+impl StateKey for u128 {
+    fn fits(num_fields: usize) -> bool {
+        num_fields <= 25
+    }
+
+    fn pack(fields: &[u8]) -> Self {
+        fields
+            .iter()
+            .enumerate()
+            .fold(0, |key, (idx, &value)| key | (value as u128) << (5 * idx))
+    }
+
+    fn unpack_into(&self, fields: &mut [u8]) {
+        for (idx, field) in fields.iter_mut().enumerate() {
+            *field = (self >> (5 * idx)) as u8 & 0x1f;
+        }
+    }
+}
+
+/// One byte per field for all larger boards.
+impl StateKey for Box<[u8]> {
+    fn fits(_num_fields: usize) -> bool {
+        true
+    }
+
+    fn pack(fields: &[u8]) -> Self {
+        fields.into()
+    }
+
+    fn unpack_into(&self, fields: &mut [u8]) {
+        fields.copy_from_slice(self);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::initialize_fields;
+
+    fn assert_round_trip<K: StateKey>(num_fields: usize) {
+        assert!(K::fits(num_fields));
+
+        let mut fields = initialize_fields(num_fields).unwrap();
+        fields.reverse();
+        let key = K::pack(&fields);
+
+        let mut unpacked = vec![0; num_fields];
+        key.unpack_into(&mut unpacked);
+        assert_eq!(unpacked, fields);
+
+        // Neighbouring states must have different keys
+        fields.swap(0, 1);
+        assert!(K::pack(&fields) != key);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        assert_round_trip::<u64>(4);
+        assert_round_trip::<u64>(16);
+        assert_round_trip::<u128>(25);
+        assert_round_trip::<Box<[u8]>>(36);
+    }
+
+    #[test]
+    fn test_fits() {
+        assert!(!u64::fits(17));
+        assert!(!u128::fits(26));
+    }
+}
@@ -43,13 +43,32 @@ pub(crate) mod examples {
     pub(crate) const SHUFFLED_TWENTY_STEPS_36_2: [u8; 36] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 19, 20, 26, 21, 22, 23, 35, 31, 25, 27, 28, 29, 24, 18, 30, 32, 33, 34];
     pub(crate) const SHUFFLED_TWENTY_STEPS_36_3: [u8; 36] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 18, 14, 15, 16, 17, 24, 12, 20, 21, 22, 23, 31, 30, 25, 26, 27, 28, 19, 35, 32, 33, 34, 29];
 
+    /// Shuffle a board of any size with a seeded random walk of the empty field.
+    pub(crate) fn shuffled_fields(width: usize, height: usize, num_swaps: usize, seed: u64) -> Vec<u8> {
+        use rand::{rngs::StdRng, seq::IteratorRandom, SeedableRng};
+
+        use crate::board::{get_swappable_neighbours, initialize_fields};
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut fields = initialize_fields(width * height).unwrap();
+        let mut empty_field_idx = fields.len() - 1;
+        for _ in 0..num_swaps {
+            let neighbour_idx = get_swappable_neighbours(width, height, empty_field_idx)
+                .choose(&mut rng)
+                .unwrap();
+            fields.swap(empty_field_idx, neighbour_idx);
+            empty_field_idx = neighbour_idx;
+        }
+        fields
+    }
+
     /// Generate a random shuffled field.
     #[test]
     fn generate_random_field() {
         use crate::board::{get_shuffle_sequence, initialize_fields};
 
         let sequence = get_shuffle_sequence(4, 4 * 4 - 1, 3);
-        let mut fields = initialize_fields(4 * 4);
+        let mut fields = initialize_fields::<u8>(4 * 4).unwrap();
 
         for swap in sequence {
             fields.swap(swap.0, swap.1);
@@ -59,25 +78,68 @@ pub(crate) mod examples {
     }
 }
 
+#[cfg(test)]
+pub(crate) mod state_space {
+    use std::collections::{HashMap, VecDeque};
+
+    use crate::{
+        board::{get_empty_field_idx, initialize_fields},
+        solver::move_metric::{apply_slide, get_move_targets, MoveMetric, Slide},
+    };
+
+    /// Compute the exact distance of every reachable state to the goal.
+    pub(crate) fn all_distances(width: usize, height: usize) -> HashMap<Vec<u8>, usize> {
+        all_distances_with_metric(width, height, MoveMetric::SingleTile)
+    }
+
+    /// Compute the exact distance in a metric of every reachable state to the
+    /// goal.
+    pub(crate) fn all_distances_with_metric(
+        width: usize,
+        height: usize,
+        metric: MoveMetric,
+    ) -> HashMap<Vec<u8>, usize> {
+        let goal = initialize_fields(width * height).unwrap();
+        let mut distances = HashMap::from([(goal.clone(), 0)]);
+        let mut to_explore = VecDeque::from([goal]);
+
+        while let Some(fields) = to_explore.pop_front() {
+            let distance = distances[&fields];
+            let empty_field_idx = get_empty_field_idx(&fields).unwrap();
+            for target_idx in get_move_targets(width, height, empty_field_idx, metric) {
+                let mut next_fields = fields.clone();
+                let slide = Slide {
+                    from: empty_field_idx,
+                    to: target_idx,
+                };
+                apply_slide(&mut next_fields, slide, width);
+                if !distances.contains_key(&next_fields) {
+                    distances.insert(next_fields.clone(), distance + 1);
+                    to_explore.push_back(next_fields);
+                }
+            }
+        }
+
+        distances
+    }
+}
+
 #[cfg(test)]
 mod dac_tests {
-    use crate::{board::initialize_fields, solver::divide_and_conquer::DacPuzzleSolver};
+    use crate::{
+        board::initialize_fields,
+        solver::{divide_and_conquer::DacPuzzleSolver, verify::verify_solution},
+    };
 
     macro_rules! dac_can_solve_test {
         ($size:expr; $($examples:expr),+) => {
             use super::examples::*;
 
-            let solved = initialize_fields($size * $size);
             for fields in [$($examples,)+] {
-                let mut fields: Vec<_> = fields.into();
                 let mut solver = DacPuzzleSolver::new(&fields, $size, $size).unwrap();
                 let swaps = solver.solve_puzzle().unwrap();
 
-                for swap in swaps {
-                    fields.swap(swap.0, swap.1);
-                }
-
-                assert_eq!(&fields, &solved);
+                verify_solution(&fields, $size, $size, &swaps).unwrap();
             }
         };
     }
@@ -133,26 +195,153 @@ mod dac_tests {
             SHUFFLED_TWENTY_STEPS_36_3
         );
     }
+
+    #[test]
+    fn dac_can_solve_rectangular() {
+        use super::examples::shuffled_fields;
+
+        for (width, height) in [
+            (3, 4),
+            (4, 3),
+            (3, 5),
+            (5, 3),
+            (4, 6),
+            (6, 4),
+            (8, 3),
+            (3, 8),
+        ] {
+            for seed in 0..20 {
+                let fields = shuffled_fields(width, height, 500, seed);
+                let mut solver =
+                    DacPuzzleSolver::new(&fields, width as i32, height as i32).unwrap();
+                let swaps = solver.solve_puzzle().unwrap();
+
+                verify_solution(&fields, width, height, &swaps)
+                    .unwrap_or_else(|err| panic!("{width}x{height} seed {seed}: {err}"));
+            }
+        }
+    }
+
+    #[test]
+    fn dac_can_solve_strips() {
+        use super::examples::shuffled_fields;
+
+        for (width, height) in [(2, 2), (2, 3), (3, 2), (2, 4), (4, 2), (2, 8), (8, 2)] {
+            for seed in 0..20 {
+                let fields = shuffled_fields(width, height, 500, seed);
+                let mut solver =
+                    DacPuzzleSolver::new(&fields, width as i32, height as i32).unwrap();
+                let swaps = solver.solve_puzzle().unwrap();
+
+                verify_solution(&fields, width, height, &swaps)
+                    .unwrap_or_else(|err| panic!("{width}x{height} seed {seed}: {err}"));
+            }
+        }
+    }
+
+    #[test]
+    fn dac_rejects_single_line() {
+        use crate::error::LibError;
+
+        for (width, height) in [(1, 4), (4, 1), (1, 1)] {
+            let fields = initialize_fields::<u8>(width * height).unwrap();
+            assert!(matches!(
+                DacPuzzleSolver::new(&fields, width as i32, height as i32),
+                Err(LibError::BoardTooSmall { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn dac_can_solve_large_boards() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        use crate::solver::generator::random_solvable_fields;
+
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // The largest board with `u8` field IDs
+        let fields: Vec<u8> = random_solvable_fields(16, 16, &mut rng).unwrap();
+        let mut solver = DacPuzzleSolver::new(&fields, 16, 16).unwrap();
+        let swaps = solver.solve_puzzle().unwrap();
+        verify_solution(&fields, 16, 16, &swaps).unwrap();
+
+        for (width, height) in [(17, 17), (20, 32), (32, 32)] {
+            let fields: Vec<u16> = random_solvable_fields(width, height, &mut rng).unwrap();
+            let mut solver = DacPuzzleSolver::new(&fields, width as i32, height as i32).unwrap();
+            let swaps = solver.solve_puzzle().unwrap();
+
+            verify_solution(&fields, width, height, &swaps)
+                .unwrap_or_else(|err| panic!("{width}x{height}: {err}"));
+        }
+    }
+
+    #[test]
+    fn dac_rejects_too_many_fields() {
+        use crate::{board::TileId, error::LibError};
+
+        // IDs which do not fit are never truncated
+        assert_eq!(u8::from_usize(255), Some(255));
+        assert_eq!(u8::from_usize(256), None);
+        assert_eq!(u16::from_usize(32 * 32), Some(1024));
+        assert_eq!(u16::from_usize(1 << 16), None);
+
+        assert!(matches!(
+            initialize_fields::<u8>(17 * 17),
+            Err(LibError::TooManyFields {
+                num_fields: 289,
+                max_num_fields: 256
+            })
+        ));
+
+        // The ID 288 of the empty field cannot be represented by `u8`
+        let fields = vec![0u8; 17 * 17];
+        assert!(matches!(
+            DacPuzzleSolver::new(&fields, 17, 17),
+            Err(LibError::TooManyFields { .. })
+        ));
+    }
+
+    #[test]
+    fn dac_stages_match_solve_puzzle() {
+        use super::examples::*;
+
+        let examples: [(&[u8], i32); 4] = [
+            (&SHUFFLED_TWENTY_STEPS_9_1, 3),
+            (&SHUFFLED_TWENTY_STEPS_16_2, 4),
+            (&SHUFFLED_FIFTEEN_STEPS_16_3, 4),
+            (&SHUFFLED_TWENTY_STEPS_36_1, 6),
+        ];
+
+        for (fields, size) in examples {
+            let mut solver = DacPuzzleSolver::new(fields, size, size).unwrap();
+            let expected = solver.solve_puzzle().unwrap();
+
+            let mut solver = DacPuzzleSolver::new(fields, size, size).unwrap();
+            let stages: Vec<_> = solver.stages().collect::<Result<_, _>>().unwrap();
+            // Alternating rows and columns with their corners, then the last
+            // fields
+            assert_eq!(stages.len(), 2 * (2 * size as usize - 3) + 1);
+            assert_eq!(stages.concat(), expected);
+
+            // Solving after all stages adds no further swaps
+            assert_eq!(solver.solve_puzzle().unwrap(), expected);
+        }
+    }
 }
 
 #[cfg(test)]
 mod optimal_tests {
-    use crate::{board::initialize_fields, solver::optimal::find_swap_order};
+    use crate::solver::{optimal::find_swap_order, verify::verify_solution};
 
     macro_rules! optimal_can_solve_test {
         ($size:expr; $($examples:expr),+) => {
             use super::examples::*;
 
-            let solved = initialize_fields($size * $size);
             for fields in [$($examples,)+] {
-                let mut fields: Vec<_> = fields.into();
                 let swaps = find_swap_order(&fields, $size, $size, 10_000_000_000).unwrap();
 
-                for swap in swaps {
-                    fields.swap(swap.0, swap.1);
-                }
-
-                assert_eq!(&fields, &solved);
+                verify_solution(&fields, $size, $size, &swaps).unwrap();
             }
         };
     }
@@ -178,6 +367,29 @@ mod optimal_tests {
         );
     }
 
+    #[test]
+    fn bidirectional_matches_ida_star() {
+        use super::examples::*;
+        use crate::solver::{ida_star, optimal::find_swap_order_bidirectional};
+
+        for (fields, size) in [
+            (&SHUFFLED_TEN_STEPS_9_1[..], 3),
+            (&SHUFFLED_FIFTEEN_STEPS_9_2[..], 3),
+            (&SHUFFLED_TWENTY_STEPS_9_1[..], 3),
+            (&SHUFFLED_TWENTY_STEPS_9_2[..], 3),
+            (&SHUFFLED_TWENTY_STEPS_9_3[..], 3),
+            (&SHUFFLED_FIFTEEN_STEPS_16_1[..], 4),
+            (&SHUFFLED_TWENTY_STEPS_16_1[..], 4),
+            (&SHUFFLED_TWENTY_STEPS_16_2[..], 4),
+            (&SHUFFLED_TWENTY_STEPS_16_3[..], 4),
+        ] {
+            let expected = ida_star::find_swap_order(fields, size, size, 10_000_000).unwrap();
+            let swaps = find_swap_order_bidirectional(fields, size, size, 10_000_000).unwrap();
+            assert_eq!(swaps.len(), expected.len());
+            verify_solution(fields, size, size, &swaps).unwrap();
+        }
+    }
+
     #[test]
     fn optimal_can_solve_4x4() {
         optimal_can_solve_test!(
@@ -193,3 +405,1214 @@ mod optimal_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod ida_star_tests {
+    use crate::solver::{ida_star::find_swap_order, optimal, verify::verify_solution};
+
+    macro_rules! ida_star_can_solve_test {
+        ($size:expr; $($examples:expr),+) => {
+            use super::examples::*;
+
+            for fields in [$($examples,)+] {
+                let swaps = find_swap_order(&fields, $size, $size, 10_000_000_000).unwrap();
+
+                verify_solution(&fields, $size, $size, &swaps).unwrap();
+            }
+        };
+    }
+
+    #[test]
+    fn ida_star_can_solve_3x3() {
+        ida_star_can_solve_test!(
+            3;
+            SHUFFLED_ONE_STEPS_9_1,
+            SHUFFLED_ONE_STEPS_9_2,
+            SHUFFLED_THREE_STEPS_9_1,
+            SHUFFLED_THREE_STEPS_9_2,
+            SHUFFLED_THREE_STEPS_9_3,
+            SHUFFLED_TEN_STEPS_9_1,
+            SHUFFLED_TEN_STEPS_9_2,
+            SHUFFLED_TEN_STEPS_9_3,
+            SHUFFLED_FIFTEEN_STEPS_9_1,
+            SHUFFLED_FIFTEEN_STEPS_9_2,
+            SHUFFLED_FIFTEEN_STEPS_9_3,
+            SHUFFLED_TWENTY_STEPS_9_1,
+            SHUFFLED_TWENTY_STEPS_9_2,
+            SHUFFLED_TWENTY_STEPS_9_3
+        );
+    }
+
+    #[test]
+    fn ida_star_can_solve_4x4() {
+        ida_star_can_solve_test!(
+            4;
+            SHUFFLED_ONE_STEP_16_1,
+            SHUFFLED_ONE_STEP_16_2,
+            SHUFFLED_THREE_STEPS_16_1,
+            SHUFFLED_THREE_STEPS_16_2,
+            SHUFFLED_THREE_STEPS_16_3,
+            SHUFFLED_TEN_STEPS_16_1,
+            SHUFFLED_TEN_STEPS_16_2,
+            SHUFFLED_TEN_STEPS_16_3,
+            SHUFFLED_FIFTEEN_STEPS_16_1,
+            SHUFFLED_FIFTEEN_STEPS_16_2,
+            SHUFFLED_FIFTEEN_STEPS_16_3,
+            SHUFFLED_TWENTY_STEPS_16_1,
+            SHUFFLED_TWENTY_STEPS_16_2,
+            SHUFFLED_TWENTY_STEPS_16_3
+        );
+    }
+
+    #[test]
+    fn ida_star_matches_optimal_length() {
+        use super::examples::*;
+
+        for fields in [
+            SHUFFLED_TEN_STEPS_9_1,
+            SHUFFLED_FIFTEEN_STEPS_9_2,
+            SHUFFLED_TWENTY_STEPS_9_3,
+        ] {
+            let expected = optimal::find_swap_order(&fields, 3, 3, 10_000_000).unwrap();
+            let swaps = find_swap_order(&fields, 3, 3, 10_000_000).unwrap();
+            assert_eq!(swaps.len(), expected.len());
+        }
+
+        for fields in [SHUFFLED_TEN_STEPS_16_1, SHUFFLED_TEN_STEPS_16_3] {
+            let expected = optimal::find_swap_order(&fields, 4, 4, 10_000_000).unwrap();
+            let swaps = find_swap_order(&fields, 4, 4, 10_000_000).unwrap();
+            assert_eq!(swaps.len(), expected.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod lookup_table_tests {
+    use crate::solver::{
+        lookup_table::LookupTable, optimal::find_swap_order, verify::verify_solution,
+    };
+
+    #[test]
+    fn lookup_table_matches_optimal() {
+        use super::examples::*;
+
+        let table = LookupTable::embedded_3x3();
+        for fields in [
+            SHUFFLED_TWENTY_STEPS_9_1,
+            SHUFFLED_TWENTY_STEPS_9_2,
+            SHUFFLED_TWENTY_STEPS_9_3,
+            [8, 5, 2, 1, 0, 7, 6, 4, 3],
+        ] {
+            let swaps = table.find_swap_order(&fields).unwrap();
+            let optimal = find_swap_order(&fields, 3, 3, 1_000_000).unwrap();
+            assert_eq!(swaps.len(), optimal.len());
+            assert_eq!(table.distance(&fields).unwrap(), optimal.len());
+            verify_solution(&fields, 3, 3, &swaps).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod hint_tests {
+    use super::examples::shuffled_fields;
+    use crate::{
+        board::initialize_fields,
+        solver::{hint::best_next_move, optimal::find_swap_order},
+    };
+
+    #[test]
+    fn hint_distance_is_optimal() {
+        use super::examples::*;
+
+        let examples: [(&[u8], usize); 4] = [
+            (&SHUFFLED_TWENTY_STEPS_9_1, 3),
+            (&SHUFFLED_TWENTY_STEPS_9_2, 3),
+            (&SHUFFLED_FIFTEEN_STEPS_16_1, 4),
+            (&SHUFFLED_TWENTY_STEPS_16_2, 4),
+        ];
+
+        for (fields, size) in examples {
+            let hint = best_next_move(fields, size, size, None).unwrap();
+            let optimal = find_swap_order(fields, size, size, 10_000_000).unwrap();
+            assert!(hint.exact);
+            assert_eq!(hint.distance, optimal.len());
+
+            // The remaining distance after the hinted swap is one less
+            let (empty_idx_before, empty_idx_after) = hint.swap.unwrap();
+            let mut next_fields = fields.to_vec();
+            next_fields.swap(empty_idx_before, empty_idx_after);
+            let next_hint = best_next_move(&next_fields, size, size, None).unwrap();
+            assert_eq!(next_hint.distance, hint.distance - 1);
+        }
+    }
+
+    #[test]
+    fn following_hints_solves_large_boards() {
+        for (width, height) in [(6, 6), (7, 5)] {
+            let mut fields = shuffled_fields(width, height, 1000, 3);
+
+            // Following the hints gets closer to the goal with every swap
+            let mut hint = best_next_move(&fields, width, height, None).unwrap();
+            assert!(!hint.exact);
+            while let Some((empty_idx_before, empty_idx_after)) = hint.swap {
+                fields.swap(empty_idx_before, empty_idx_after);
+                let next_hint = best_next_move(&fields, width, height, None).unwrap();
+                assert!(next_hint.distance < hint.distance);
+                hint = next_hint;
+            }
+            assert_eq!(fields, initialize_fields(width * height).unwrap());
+        }
+    }
+}
+
+#[cfg(test)]
+mod heuristic_tests {
+    use crate::solver::{
+        heuristic::{CornerTiles, Heuristic, LastMoves, LinearConflict, ManhattanDistance, Max},
+        ida_star::find_swap_order_with_heuristic,
+    };
+
+    /// Solve all examples and return the total number of expanded states.
+    fn total_expansions<H: Heuristic>(examples: &[&[u8]], size: usize, heuristic: H) -> usize {
+        let manhattan = ManhattanDistance::new(size, size);
+        examples
+            .iter()
+            .map(|fields| {
+                let outcome =
+                    find_swap_order_with_heuristic(fields, size, size, 10_000_000, &heuristic)
+                        .unwrap();
+                let reference =
+                    find_swap_order_with_heuristic(fields, size, size, 10_000_000, &manhattan)
+                        .unwrap();
+                assert_eq!(outcome.swaps.len(), reference.swaps.len());
+                outcome.num_expanded
+            })
+            .sum()
+    }
+
+    fn assert_expansions_saved(examples: &[&[u8]], size: usize) {
+        let manhattan = total_expansions(examples, size, ManhattanDistance::new(size, size));
+        let linear_conflict = total_expansions(examples, size, LinearConflict::new(size, size));
+        let corner_tiles = total_expansions(examples, size, CornerTiles::new(size, size));
+        let last_moves = total_expansions(examples, size, LastMoves::new(size, size));
+        let combined = total_expansions(
+            examples,
+            size,
+            Max(
+                Max(
+                    LinearConflict::new(size, size),
+                    CornerTiles::new(size, size),
+                ),
+                LastMoves::new(size, size),
+            ),
+        );
+
+        assert!(linear_conflict <= manhattan);
+        assert!(corner_tiles <= manhattan);
+        assert!(last_moves <= manhattan);
+        assert!(combined <= linear_conflict);
+    }
+
+    #[test]
+    fn heuristics_save_expansions_3x3() {
+        use super::examples::*;
+
+        assert_expansions_saved(
+            &[
+                &SHUFFLED_TEN_STEPS_9_1,
+                &SHUFFLED_TEN_STEPS_9_2,
+                &SHUFFLED_TEN_STEPS_9_3,
+                &SHUFFLED_FIFTEEN_STEPS_9_1,
+                &SHUFFLED_FIFTEEN_STEPS_9_2,
+                &SHUFFLED_FIFTEEN_STEPS_9_3,
+                &SHUFFLED_TWENTY_STEPS_9_1,
+                &SHUFFLED_TWENTY_STEPS_9_2,
+                &SHUFFLED_TWENTY_STEPS_9_3,
+            ],
+            3,
+        );
+    }
+
+    #[test]
+    fn heuristics_save_expansions_4x4() {
+        use super::examples::*;
+
+        assert_expansions_saved(
+            &[
+                &SHUFFLED_TEN_STEPS_16_1,
+                &SHUFFLED_TEN_STEPS_16_2,
+                &SHUFFLED_TEN_STEPS_16_3,
+                &SHUFFLED_FIFTEEN_STEPS_16_1,
+                &SHUFFLED_FIFTEEN_STEPS_16_2,
+                &SHUFFLED_FIFTEEN_STEPS_16_3,
+                &SHUFFLED_TWENTY_STEPS_16_1,
+                &SHUFFLED_TWENTY_STEPS_16_2,
+                &SHUFFLED_TWENTY_STEPS_16_3,
+            ],
+            4,
+        );
+    }
+}
+
+#[cfg(test)]
+mod pattern_database_tests {
+    use crate::solver::{
+        heuristic::{Heuristic, ManhattanDistance},
+        ida_star::find_swap_order_with_heuristic,
+        pattern_database::PatternDatabase,
+    };
+
+    fn assert_solves_optimally(examples: &[&[u8]], size: usize, database: &PatternDatabase) {
+        let manhattan = ManhattanDistance::new(size, size);
+        for fields in examples {
+            let expected =
+                find_swap_order_with_heuristic(fields, size, size, 10_000_000, &manhattan).unwrap();
+            let outcome =
+                find_swap_order_with_heuristic(fields, size, size, 10_000_000, database).unwrap();
+
+            assert!(database.estimate(fields) <= expected.swaps.len());
+            assert_eq!(outcome.swaps.len(), expected.swaps.len());
+            assert!(outcome.num_expanded <= expected.num_expanded);
+        }
+    }
+
+    #[test]
+    fn pattern_database_solves_3x3() {
+        use super::examples::*;
+
+        let database = PatternDatabase::build(3, 3, &[&[0, 1, 2, 3], &[4, 5, 6, 7]]).unwrap();
+        assert_solves_optimally(
+            &[
+                &SHUFFLED_TEN_STEPS_9_1,
+                &SHUFFLED_FIFTEEN_STEPS_9_2,
+                &SHUFFLED_TWENTY_STEPS_9_1,
+                &SHUFFLED_TWENTY_STEPS_9_2,
+                &SHUFFLED_TWENTY_STEPS_9_3,
+            ],
+            3,
+            &database,
+        );
+    }
+
+    #[test]
+    fn pattern_database_solves_4x4() {
+        use super::examples::*;
+
+        let database = PatternDatabase::build(
+            4,
+            4,
+            &[
+                &[0, 1, 4],
+                &[2, 3, 7],
+                &[5, 6, 9],
+                &[8, 12, 13],
+                &[10, 11, 14],
+            ],
+        )
+        .unwrap();
+        let database = PatternDatabase::from_bytes(&database.to_bytes()).unwrap();
+        assert_solves_optimally(
+            &[
+                &SHUFFLED_TEN_STEPS_16_1,
+                &SHUFFLED_FIFTEEN_STEPS_16_2,
+                &SHUFFLED_TWENTY_STEPS_16_1,
+                &SHUFFLED_TWENTY_STEPS_16_2,
+                &SHUFFLED_TWENTY_STEPS_16_3,
+            ],
+            4,
+            &database,
+        );
+    }
+}
+
+#[cfg(test)]
+mod walking_distance_tests {
+    use crate::solver::{
+        heuristic::{LinearConflict, ManhattanDistance, Max},
+        ida_star::find_swap_order_with_heuristic,
+        walking_distance::WalkingDistance,
+    };
+
+    #[test]
+    fn walking_distance_solves_4x4() {
+        use super::examples::*;
+
+        let walking_distance = WalkingDistance::new(4, 4).unwrap();
+        let combined = Max(walking_distance.clone(), LinearConflict::new(4, 4));
+        let manhattan = ManhattanDistance::new(4, 4);
+
+        for fields in [
+            SHUFFLED_TEN_STEPS_16_2,
+            SHUFFLED_FIFTEEN_STEPS_16_1,
+            SHUFFLED_FIFTEEN_STEPS_16_3,
+            SHUFFLED_TWENTY_STEPS_16_1,
+            SHUFFLED_TWENTY_STEPS_16_2,
+        ] {
+            let expected =
+                find_swap_order_with_heuristic(&fields, 4, 4, 10_000_000, &manhattan).unwrap();
+            let outcome =
+                find_swap_order_with_heuristic(&fields, 4, 4, 10_000_000, &walking_distance)
+                    .unwrap();
+            let combined_outcome =
+                find_swap_order_with_heuristic(&fields, 4, 4, 10_000_000, &combined).unwrap();
+
+            assert_eq!(outcome.swaps.len(), expected.swaps.len());
+            assert_eq!(combined_outcome.swaps.len(), expected.swaps.len());
+            assert!(combined_outcome.num_expanded <= outcome.num_expanded);
+        }
+    }
+}
+
+#[cfg(test)]
+mod progress_tests {
+    use std::ops::ControlFlow;
+
+    use crate::{
+        error::LibError,
+        solver::{
+            progress::Progress,
+            registry::{Puzzle, SolverConfig, SolverRegistry},
+        },
+    };
+
+    /// Far shuffled 4x4 puzzle which takes the searching solvers long.
+    const FAR_SHUFFLED_16: [u8; 16] = [13, 14, 7, 11, 9, 10, 8, 12, 1, 5, 4, 0, 2, 6, 3, 15];
+
+    #[test]
+    fn test_cancel_all_solvers() {
+        let puzzle = Puzzle::new(FAR_SHUFFLED_16.to_vec(), 4, 4).unwrap();
+        // Without suboptimality, weighted A* takes long as well
+        let registry = SolverRegistry::new(SolverConfig {
+            epsilon: 0.,
+            ..Default::default()
+        });
+
+        for solver in registry.supporting(4, 4) {
+            let mut num_reports = 0;
+            let mut observer = |_: &Progress| {
+                num_reports += 1;
+                ControlFlow::Break(())
+            };
+
+            let result = solver.solve_with_progress(&puzzle, &mut observer);
+            assert!(
+                matches!(result, Err(LibError::Cancelled)),
+                "{}",
+                solver.info().name
+            );
+            assert_eq!(num_reports, 1);
+        }
+    }
+
+    #[test]
+    fn test_progress_increases() -> Result<(), LibError> {
+        let puzzle = Puzzle::new(FAR_SHUFFLED_16.to_vec(), 4, 4)?;
+        let registry = SolverRegistry::default();
+        let solver = registry.get("divide-and-conquer").unwrap();
+
+        let mut reports: Vec<Progress> = Vec::new();
+        let mut observer = |progress: &Progress| {
+            reports.push(*progress);
+            ControlFlow::Continue(())
+        };
+        let solution = solver.solve_with_progress(&puzzle, &mut observer)?;
+
+        assert!(!reports.is_empty());
+        for pair in reports.windows(2) {
+            assert!(pair[0].num_expanded < pair[1].num_expanded);
+            assert!(pair[0].depth <= pair[1].depth);
+            assert!(pair[0].elapsed <= pair[1].elapsed);
+        }
+        assert!(reports.last().unwrap().depth <= solution.swaps.len());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod peephole_tests {
+    use crate::solver::{
+        divide_and_conquer::DacPuzzleSolver,
+        optimal::find_swap_order,
+        peephole::{optimize, DEFAULT_WINDOW_SIZE},
+        verify::verify_solution,
+    };
+
+    #[test]
+    fn peephole_keeps_dac_solutions_valid() {
+        use super::examples::*;
+
+        let examples: [(&[u8], usize); 5] = [
+            (&SHUFFLED_TWENTY_STEPS_9_1, 3),
+            (&SHUFFLED_TWENTY_STEPS_9_2, 3),
+            (&SHUFFLED_FIFTEEN_STEPS_16_1, 4),
+            (&SHUFFLED_TWENTY_STEPS_16_2, 4),
+            (&SHUFFLED_TWENTY_STEPS_36_1, 6),
+        ];
+
+        for (fields, size) in examples {
+            let mut solver = DacPuzzleSolver::new(fields, size as i32, size as i32).unwrap();
+            let swaps = solver.solve_puzzle().unwrap();
+            let outcome = optimize(fields, size, size, &swaps, DEFAULT_WINDOW_SIZE).unwrap();
+
+            assert_eq!(outcome.swaps.len() + outcome.num_saved, swaps.len());
+
+            verify_solution(fields, size, size, &outcome.swaps).unwrap();
+
+            if size == 3 {
+                let optimal = find_swap_order(fields, size, size, 1_000_000).unwrap();
+                assert!(outcome.swaps.len() >= optimal.len());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod goal_tests {
+    use crate::{
+        board::initialize_fields,
+        error::LibError,
+        solver::{
+            divide_and_conquer::DacPuzzleSolver,
+            optimal::{find_swap_order_bidirectional_to_goal, find_swap_order_to_goal},
+        },
+    };
+
+    /// Goal with the empty field in the top left corner.
+    const BLANK_TOP_LEFT_9: [u8; 9] = [8, 0, 1, 2, 3, 4, 5, 6, 7];
+    /// Goals with the fields in a spiral ending with the empty field. The 3x3
+    /// spiral cannot be reached from the solved state.
+    const SNAIL_9: [u8; 9] = [0, 1, 2, 7, 8, 3, 6, 5, 4];
+    #[rustfmt::skip]
+    const SNAIL_16: [u8; 16] = [0, 1, 2, 3, 11, 12, 13, 4, 10, 15, 14, 5, 9, 8, 7, 6];
+
+    fn apply_swaps(fields: &[u8], swaps: &[(usize, usize)]) -> Vec<u8> {
+        let mut fields = fields.to_vec();
+        for (empty_idx_before, empty_idx_after) in swaps {
+            fields.swap(*empty_idx_before, *empty_idx_after);
+        }
+        fields
+    }
+
+    fn assert_all_reach_goal(fields: &[u8], goal: &[u8], width: usize, height: usize) {
+        let optimal = find_swap_order_to_goal(fields, goal, width, height, 1_000_000).unwrap();
+        assert_eq!(apply_swaps(fields, &optimal), goal);
+
+        let bidirectional =
+            find_swap_order_bidirectional_to_goal(fields, goal, width, height, 1_000_000).unwrap();
+        assert_eq!(apply_swaps(fields, &bidirectional), goal);
+        assert_eq!(bidirectional.len(), optimal.len());
+
+        let mut solver =
+            DacPuzzleSolver::with_goal(fields, goal, width as i32, height as i32).unwrap();
+        let swaps = solver.solve_puzzle().unwrap();
+        assert_eq!(apply_swaps(fields, &swaps), goal);
+        assert!(swaps.len() >= optimal.len());
+    }
+
+    #[test]
+    fn goal_blank_top_left() {
+        use super::examples::*;
+
+        for fields in [
+            SHUFFLED_ONE_STEPS_9_1,
+            SHUFFLED_TEN_STEPS_9_1,
+            SHUFFLED_TWENTY_STEPS_9_2,
+        ] {
+            assert_all_reach_goal(&fields, &BLANK_TOP_LEFT_9, 3, 3);
+        }
+        assert_all_reach_goal(&initialize_fields(9).unwrap(), &BLANK_TOP_LEFT_9, 3, 3);
+        assert_all_reach_goal(&BLANK_TOP_LEFT_9, &BLANK_TOP_LEFT_9, 3, 3);
+    }
+
+    #[test]
+    fn goal_snail() {
+        use super::examples::*;
+
+        let near_snail = apply_swaps(&SNAIL_16, &[(9, 5), (5, 6), (6, 10), (10, 14)]);
+        assert_all_reach_goal(&near_snail, &SNAIL_16, 4, 4);
+
+        for fields in [
+            SHUFFLED_TWENTY_STEPS_16_1,
+            SHUFFLED_TWENTY_STEPS_16_2,
+            SHUFFLED_TWENTY_STEPS_16_3,
+        ] {
+            let mut solver = DacPuzzleSolver::with_goal(&fields, &SNAIL_16, 4, 4).unwrap();
+            let swaps = solver.solve_puzzle().unwrap();
+            assert_eq!(apply_swaps(&fields, &swaps), SNAIL_16);
+        }
+    }
+
+    #[test]
+    fn goal_checkpoint() {
+        use super::examples::shuffled_fields;
+
+        // Both states are reachable from the solved state and therefore from
+        // each other
+        for (width, height) in [(3, 3), (2, 4), (4, 2)] {
+            let fields = shuffled_fields(width, height, 100, 0);
+            let checkpoint = shuffled_fields(width, height, 100, 1);
+            assert_all_reach_goal(&fields, &checkpoint, width, height);
+        }
+
+        for (width, height) in [(5, 5), (3, 7), (7, 3)] {
+            let fields = shuffled_fields(width, height, 1000, 0);
+            let checkpoint = shuffled_fields(width, height, 1000, 1);
+            let mut solver =
+                DacPuzzleSolver::with_goal(&fields, &checkpoint, width as i32, height as i32)
+                    .unwrap();
+            let swaps = solver.solve_puzzle().unwrap();
+            assert_eq!(apply_swaps(&fields, &swaps), checkpoint);
+        }
+    }
+
+    #[test]
+    fn goal_unreachable() {
+        let fields = initialize_fields(9).unwrap();
+        let mut goal = BLANK_TOP_LEFT_9;
+        goal.swap(1, 2);
+
+        assert!(matches!(
+            find_swap_order_to_goal(&fields, &goal, 3, 3, 1_000_000),
+            Err(LibError::GoalUnreachable)
+        ));
+        assert!(matches!(
+            find_swap_order_bidirectional_to_goal(&fields, &goal, 3, 3, 1_000_000),
+            Err(LibError::GoalUnreachable)
+        ));
+        assert!(matches!(
+            DacPuzzleSolver::with_goal(&fields, &goal, 3, 3),
+            Err(LibError::GoalUnreachable)
+        ));
+        assert!(matches!(
+            DacPuzzleSolver::with_goal(&fields, &SNAIL_9, 3, 3),
+            Err(LibError::GoalUnreachable)
+        ));
+
+        // The fields of a single line can never change their order
+        let fields = initialize_fields(4).unwrap();
+        let goal = [1, 2, 0, 3];
+        assert!(matches!(
+            find_swap_order_to_goal(&fields, &goal, 4, 1, 1_000_000),
+            Err(LibError::GoalUnreachable)
+        ));
+        assert!(matches!(
+            find_swap_order_bidirectional_to_goal(&fields, &goal, 4, 1, 1_000_000),
+            Err(LibError::GoalUnreachable)
+        ));
+    }
+
+    #[test]
+    fn goal_must_match_fields() {
+        let fields = initialize_fields(4).unwrap();
+        assert!(matches!(
+            find_swap_order_to_goal(&fields, &[0, 1, 2], 2, 2, 100),
+            Err(LibError::FieldsBoardMismatch { len: 3, .. })
+        ));
+        assert!(matches!(
+            DacPuzzleSolver::with_goal(&fields, &[0, 0, 2, 3], 2, 2),
+            Err(LibError::ValueNotFound(1))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod weighted_a_star_tests {
+    use crate::solver::{optimal::find_swap_order, verify::verify_solution, weighted_a_star};
+
+    #[test]
+    fn weighted_a_star_within_bound() {
+        use super::examples::*;
+
+        let examples: [(&[u8], usize); 6] = [
+            (&SHUFFLED_TWENTY_STEPS_9_1, 3),
+            (&SHUFFLED_TWENTY_STEPS_9_2, 3),
+            (&SHUFFLED_TWENTY_STEPS_9_3, 3),
+            (&SHUFFLED_FIFTEEN_STEPS_16_1, 4),
+            (&SHUFFLED_TWENTY_STEPS_16_1, 4),
+            (&SHUFFLED_TWENTY_STEPS_16_2, 4),
+        ];
+
+        for (fields, size) in examples {
+            let optimal = find_swap_order(fields, size, size, 10_000_000).unwrap();
+
+            for epsilon in [0., 0.5, 1., 3.] {
+                let outcome =
+                    weighted_a_star::find_swap_order(fields, size, size, epsilon, 10_000_000)
+                        .unwrap();
+
+                verify_solution(fields, size, size, &outcome.swaps).unwrap();
+
+                assert!(outcome.swaps.len() as f64 <= (1. + epsilon) * optimal.len() as f64);
+                assert!(outcome.lower_bound <= optimal.len());
+                assert!(outcome.suboptimality() <= 1. + epsilon);
+                if epsilon == 0. {
+                    assert_eq!(outcome.swaps.len(), optimal.len());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod beam_search_tests {
+    use super::examples::shuffled_fields;
+    use crate::solver::{
+        beam_search::find_swap_order, divide_and_conquer::DacPuzzleSolver, verify::verify_solution,
+    };
+
+    fn assert_not_longer_than_dac(
+        fields: &[u8],
+        width: usize,
+        height: usize,
+        beam_width: usize,
+    ) -> usize {
+        let swaps = find_swap_order(fields, width, height, beam_width).unwrap();
+
+        verify_solution(fields, width, height, &swaps).unwrap();
+
+        let mut solver = DacPuzzleSolver::new(fields, width as i32, height as i32).unwrap();
+        let dac_swaps = solver.solve_puzzle().unwrap();
+        assert!(swaps.len() <= dac_swaps.len());
+
+        swaps.len()
+    }
+
+    #[test]
+    fn beam_search_can_solve_examples() {
+        use super::examples::*;
+
+        let examples: [(&[u8], usize); 5] = [
+            (&SHUFFLED_TWENTY_STEPS_9_1, 3),
+            (&SHUFFLED_FIFTEEN_STEPS_16_1, 4),
+            (&SHUFFLED_TWENTY_STEPS_16_2, 4),
+            (&SHUFFLED_TWENTY_STEPS_36_1, 6),
+            (&SHUFFLED_TWENTY_STEPS_36_2, 6),
+        ];
+
+        for (fields, size) in examples {
+            for beam_width in [0, 1, 16] {
+                assert_not_longer_than_dac(fields, size, size, beam_width);
+            }
+        }
+    }
+
+    #[test]
+    fn beam_search_can_solve_rectangular() {
+        for (width, height) in [(2, 5), (5, 2), (4, 7), (7, 4)] {
+            let fields = shuffled_fields(width, height, 1000, 1);
+            assert_not_longer_than_dac(&fields, width, height, 16);
+        }
+    }
+
+    #[test]
+    fn beam_search_shortens_large_boards() {
+        let fields = shuffled_fields(8, 8, 10_000, 2);
+        let mut solver = DacPuzzleSolver::new(&fields, 8, 8).unwrap();
+        let num_dac_swaps = solver.solve_puzzle().unwrap().len();
+
+        let num_swaps = assert_not_longer_than_dac(&fields, 8, 8, 64);
+        assert!(num_swaps * 20 < num_dac_swaps * 19);
+    }
+}
+
+#[cfg(test)]
+mod move_metric_tests {
+    use super::{examples::*, state_space::all_distances_with_metric};
+    use crate::solver::{
+        heuristic::MultiTileManhattan,
+        ida_star,
+        move_metric::{count_moves, MoveMetric},
+        optimal,
+        progress::NoProgress,
+        registry::{Puzzle, SolverConfig, SolverRegistry},
+        verify::verify_solution,
+    };
+
+    #[test]
+    fn multi_tile_searches_agree() {
+        let metric = MoveMetric::MultiTile;
+
+        for (width, height) in [(3, 3), (2, 4), (4, 2)] {
+            let distances = all_distances_with_metric(width, height, metric);
+            let mut examples = vec![shuffled_fields(width, height, 100, 3)];
+            if (width, height) == (3, 3) {
+                examples.extend([
+                    SHUFFLED_TEN_STEPS_9_1.to_vec(),
+                    SHUFFLED_FIFTEEN_STEPS_9_2.to_vec(),
+                    SHUFFLED_TWENTY_STEPS_9_1.to_vec(),
+                    SHUFFLED_TWENTY_STEPS_9_3.to_vec(),
+                ]);
+            }
+
+            for fields in examples {
+                let expected = distances[&fields];
+                let solutions = [
+                    optimal::find_swap_order_with_metric(
+                        &fields,
+                        width,
+                        height,
+                        metric,
+                        1_000_000,
+                        &mut NoProgress,
+                    )
+                    .unwrap(),
+                    optimal::find_swap_order_bidirectional_with_metric(
+                        &fields,
+                        width,
+                        height,
+                        metric,
+                        1_000_000,
+                        &mut NoProgress,
+                    )
+                    .unwrap(),
+                    ida_star::find_swap_order_with_metric(
+                        &fields,
+                        width,
+                        height,
+                        metric,
+                        1_000_000,
+                        &MultiTileManhattan::new(width, height),
+                        &mut NoProgress,
+                    )
+                    .unwrap()
+                    .swaps,
+                ];
+
+                let num_single_tile = optimal::find_swap_order(&fields, width, height, 1_000_000)
+                    .unwrap()
+                    .len();
+                for swaps in solutions {
+                    verify_solution(&fields, width, height, &swaps).unwrap();
+                    assert_eq!(count_moves(&swaps, metric), expected);
+                    assert!(expected <= num_single_tile);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn registry_uses_metric() {
+        let registry = SolverRegistry::new(SolverConfig {
+            move_metric: MoveMetric::MultiTile,
+            ..Default::default()
+        });
+        let puzzle = Puzzle::new(SHUFFLED_TWENTY_STEPS_9_2.to_vec(), 3, 3).unwrap();
+        let expected = all_distances_with_metric(3, 3, MoveMetric::MultiTile)[&puzzle.fields];
+
+        for name in ["bfs", "bidirectional", "ida-star"] {
+            let solver = registry.get(name).unwrap();
+            assert_eq!(solver.info().metric, MoveMetric::MultiTile);
+
+            let solution = solver.solve(&puzzle).unwrap();
+            verify_solution(&puzzle.fields, 3, 3, &solution.swaps).unwrap();
+            assert_eq!(solution.num_moves(MoveMetric::MultiTile), expected);
+            assert_eq!(solution.slides().len(), expected);
+        }
+    }
+}
+
+#[cfg(test)]
+mod solvability_tests {
+    use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+    use super::state_space::all_distances;
+    use crate::{
+        board::{analyze_solvability, initialize_fields, is_goal_reachable, Solvability},
+        error::LibError,
+        solver::{
+            beam_search, divide_and_conquer::DacPuzzleSolver, ida_star, lookup_table::LookupTable,
+            optimal, weighted_a_star,
+        },
+    };
+
+    #[test]
+    fn solved_is_solvable() -> Result<(), LibError> {
+        for (width, height) in [(2, 2), (3, 3), (4, 4), (3, 5), (6, 2)] {
+            let fields = initialize_fields::<u8>(width * height).unwrap();
+            assert_eq!(
+                analyze_solvability(&fields, width, height)?,
+                Solvability {
+                    num_inversions: 0,
+                    empty_field_row_from_bottom: 0,
+                    solvable: true,
+                }
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parity_matches_reachability() -> Result<(), LibError> {
+        let mut rng = StdRng::seed_from_u64(0);
+        for (width, height) in [
+            (2, 2),
+            (3, 3),
+            (4, 4),
+            (3, 4),
+            (4, 3),
+            (5, 2),
+            (2, 5),
+            (6, 6),
+        ] {
+            let goal = initialize_fields::<u8>(width * height).unwrap();
+            let mut num_solvable = 0;
+            for _ in 0..200 {
+                let mut fields = goal.clone();
+                fields.shuffle(&mut rng);
+
+                let solvable = analyze_solvability(&fields, width, height)?.solvable;
+                assert_eq!(solvable, is_goal_reachable(&fields, &goal, width)?);
+                num_solvable += usize::from(solvable);
+            }
+            assert!((50..150).contains(&num_solvable));
+        }
+
+        // On a single line, the reachable states are the solved one with the
+        // empty field anywhere on the line
+        for (width, height) in [(4, 1), (1, 4), (5, 1), (1, 3)] {
+            let goal = initialize_fields::<u8>(width * height).unwrap();
+            let mut reachable = vec![goal.clone()];
+            for idx in (1..goal.len()).rev() {
+                let mut fields = reachable.last().unwrap().clone();
+                fields.swap(idx, idx - 1);
+                reachable.push(fields);
+            }
+            for fields in &reachable {
+                assert!(analyze_solvability(fields, width, height)?.solvable);
+            }
+
+            for _ in 0..200 {
+                let mut fields = goal.clone();
+                fields.shuffle(&mut rng);
+
+                let solvable = analyze_solvability(&fields, width, height)?.solvable;
+                assert_eq!(solvable, reachable.contains(&fields), "{fields:?}");
+                assert_eq!(solvable, is_goal_reachable(&fields, &goal, width)?);
+
+                // Any reachable state can be the goal as well
+                let other_goal = reachable.choose(&mut rng).unwrap();
+                assert_eq!(solvable, is_goal_reachable(&fields, other_goal, width)?);
+            }
+        }
+        assert!(!analyze_solvability(&[2u8, 0, 1, 3], 4, 1)?.solvable);
+        Ok(())
+    }
+
+    #[test]
+    fn all_reachable_states_are_solvable() -> Result<(), LibError> {
+        for (width, height) in [(2, 3), (3, 2)] {
+            let distances = all_distances(width, height);
+            assert_eq!(distances.len(), 360);
+            for fields in distances.keys() {
+                assert!(analyze_solvability(fields, width, height)?.solvable);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn solvers_reject_unsolvable() {
+        // Swapping two fields flips the parity
+        let mut fields = initialize_fields(16).unwrap();
+        fields.swap(0, 1);
+        let mut fields_3x3 = initialize_fields(9).unwrap();
+        fields_3x3.swap(0, 1);
+
+        let results = [
+            optimal::find_swap_order(&fields, 4, 4, 10_000_000),
+            optimal::find_swap_order_bidirectional(&fields, 4, 4, 10_000_000),
+            ida_star::find_swap_order(&fields, 4, 4, 10_000_000),
+            weighted_a_star::find_swap_order(&fields, 4, 4, 0.5, 10_000_000)
+                .map(|outcome| outcome.swaps),
+            beam_search::find_swap_order(&fields, 4, 4, 16),
+            DacPuzzleSolver::new(&fields, 4, 4).and_then(|mut solver| solver.solve_puzzle()),
+            LookupTable::embedded_3x3().find_swap_order(&fields_3x3),
+        ];
+        for result in results {
+            assert!(matches!(result, Err(LibError::Unsolvable)), "{result:?}");
+        }
+
+        // The order of the fields of a line is fixed
+        assert!(matches!(
+            ida_star::find_swap_order(&[2, 0, 1, 3], 4, 1, 10_000_000),
+            Err(LibError::Unsolvable)
+        ));
+    }
+
+    #[test]
+    fn invalid_fields() {
+        assert!(matches!(
+            analyze_solvability(&[0u8, 1, 1, 3], 2, 2),
+            Err(LibError::ValueNotFound(2))
+        ));
+        assert!(matches!(
+            analyze_solvability(&[0u8, 1, 2], 2, 2),
+            Err(LibError::FieldsBoardMismatch { len: 3, .. })
+        ));
+    }
+}
+
+#[cfg(test)]
+mod generator_tests {
+    use std::collections::HashMap;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::state_space::all_distances;
+    use crate::{
+        board::{get_shuffle_sequence, initialize_fields},
+        error::LibError,
+        solver::{
+            generator::{random_fields_at_distance, random_solvable_fields, Difficulty},
+            ida_star,
+            lookup_table::LookupTable,
+            verify::verify_solution,
+        },
+    };
+
+    /// Check the distance of a generated puzzle and that its swaps lead from
+    /// the solved state to it.
+    fn assert_puzzle_at_distance(
+        fields: &[u8],
+        swaps: &[(usize, usize)],
+        width: usize,
+        height: usize,
+        distance: usize,
+    ) {
+        assert_eq!(swaps.len(), distance);
+        let reversed: Vec<_> = swaps
+            .iter()
+            .rev()
+            .map(|&(empty_idx_before, empty_idx_after)| (empty_idx_after, empty_idx_before))
+            .collect();
+        verify_solution(fields, width, height, &reversed).unwrap();
+    }
+
+    /// Pearson's chi-square statistic of observed counts and expected
+    /// frequencies.
+    fn chi_square(observed: &[usize], expected: &[f64]) -> f64 {
+        observed
+            .iter()
+            .zip(expected)
+            .map(|(&observed, &expected)| (observed as f64 - expected).powi(2) / expected)
+            .sum()
+    }
+
+    /// Loose upper bound of the chi-square statistic of a true hypothesis, six
+    /// standard deviations above its mean.
+    fn max_chi_square(degrees_of_freedom: usize) -> f64 {
+        degrees_of_freedom as f64 + 6. * (2. * degrees_of_freedom as f64).sqrt()
+    }
+
+    #[test]
+    fn uniform_states_2x3() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let states: Vec<_> = all_distances(2, 3).into_keys().collect();
+        let state_indices: HashMap<_, _> = states
+            .iter()
+            .enumerate()
+            .map(|(idx, fields)| (fields.clone(), idx))
+            .collect();
+
+        let num_samples = 100 * states.len();
+        let mut counts = vec![0; states.len()];
+        for _ in 0..num_samples {
+            let fields = random_solvable_fields(2, 3, &mut rng).unwrap();
+            counts[state_indices[&fields]] += 1;
+        }
+
+        let expected = vec![100.; states.len()];
+        assert!(chi_square(&counts, &expected) < max_chi_square(states.len() - 1));
+    }
+
+    #[test]
+    fn uniform_positions_3x3() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let num_samples = 18_000;
+
+        // Number of times each field was drawn at each position
+        let mut counts = vec![[0; 9]; 9];
+        for _ in 0..num_samples {
+            let fields: Vec<u8> = random_solvable_fields(3, 3, &mut rng).unwrap();
+            for (idx, &value) in fields.iter().enumerate() {
+                counts[value as usize][idx] += 1;
+            }
+        }
+
+        let expected = [num_samples as f64 / 9.; 9];
+        for field_counts in counts {
+            assert!(chi_square(&field_counts, &expected) < max_chi_square(8));
+        }
+    }
+
+    #[test]
+    fn uniform_distances_3x3() {
+        // Share of the solvable states at each distance
+        let mut num_states = vec![0; 32];
+        for distance in all_distances(3, 3).into_values() {
+            num_states[distance] += 1;
+        }
+        let num_solvable: usize = num_states.iter().sum();
+        assert_eq!(num_solvable, 181_440);
+
+        let table = LookupTable::embedded_3x3();
+        let num_samples = 20_000;
+        let distance_chi_square = |samples: &mut dyn Iterator<Item = Vec<u8>>| {
+            let mut counts = vec![0; 32];
+            for fields in samples {
+                counts[table.distance(&fields).unwrap()] += 1;
+            }
+
+            // Merge the rare distances at both ends into one bin
+            let expected: Vec<f64> = num_states
+                .iter()
+                .map(|&num| (num * num_samples) as f64 / num_solvable as f64)
+                .collect();
+            let (mut frequent_counts, mut frequent_expected) = (vec![0], vec![0.]);
+            for (count, expected) in counts.into_iter().zip(expected) {
+                match expected < 5. {
+                    true => {
+                        frequent_counts[0] += count;
+                        frequent_expected[0] += expected;
+                    }
+                    false => {
+                        frequent_counts.push(count);
+                        frequent_expected.push(expected);
+                    }
+                }
+            }
+
+            let degrees_of_freedom = frequent_counts.len() - 1;
+            (
+                chi_square(&frequent_counts, &frequent_expected),
+                max_chi_square(degrees_of_freedom),
+            )
+        };
+
+        let mut rng = StdRng::seed_from_u64(2);
+        let (statistic, bound) = distance_chi_square(
+            &mut (0..num_samples).map(|_| random_solvable_fields(3, 3, &mut rng).unwrap()),
+        );
+        assert!(statistic < bound, "{statistic} >= {bound}");
+
+        // A random walk of the empty field stays close to the solved state
+        let (statistic, bound) = distance_chi_square(&mut (0..num_samples).map(|_| {
+            let mut fields = initialize_fields(9).unwrap();
+            for (empty_idx_before, empty_idx_after) in get_shuffle_sequence(3, 8, 10) {
+                fields.swap(empty_idx_before, empty_idx_after);
+            }
+            fields
+        }));
+        assert!(statistic > bound, "{statistic} <= {bound}");
+    }
+
+    #[test]
+    fn exact_distance_3x3() {
+        let table = LookupTable::embedded_3x3();
+        let mut rng = StdRng::seed_from_u64(3);
+
+        for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+            for _ in 0..10 {
+                let puzzle =
+                    random_fields_at_distance(3, 3, difficulty.distances(), 1_000_000, &mut rng)
+                        .unwrap();
+                assert!(difficulty.distances().contains(&puzzle.distance));
+                assert_eq!(table.distance(&puzzle.fields).unwrap(), puzzle.distance);
+                assert_puzzle_at_distance(&puzzle.fields, &puzzle.swaps, 3, 3, puzzle.distance);
+            }
+        }
+
+        // The hardest 3x3 puzzles
+        let puzzle = random_fields_at_distance(3, 3, 31..=31, 1_000_000, &mut rng).unwrap();
+        assert_eq!(table.distance(&puzzle.fields).unwrap(), 31);
+    }
+
+    #[test]
+    fn exact_distance_searched() {
+        let mut rng = StdRng::seed_from_u64(4);
+        for (width, height, distances) in [(4, 4, 14..=18), (3, 4, 10..=20), (5, 5, 12..=12)] {
+            let puzzle =
+                random_fields_at_distance(width, height, distances.clone(), 10_000_000, &mut rng)
+                    .unwrap();
+            assert!(distances.contains(&puzzle.distance));
+            assert_puzzle_at_distance(
+                &puzzle.fields,
+                &puzzle.swaps,
+                width,
+                height,
+                puzzle.distance,
+            );
+
+            let optimal =
+                ida_star::find_swap_order(&puzzle.fields, width, height, 10_000_000).unwrap();
+            assert_eq!(optimal.len(), puzzle.distance);
+        }
+    }
+
+    #[test]
+    fn distance_partly_out_of_reach() {
+        // 2x2 puzzles need at most 6 swaps, so 7 is never drawn successfully
+        let mut num_at_distance = [0; 2];
+        for seed in 0..50 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let puzzle = random_fields_at_distance(2, 2, 5..=7, 1_000, &mut rng).unwrap();
+            assert_puzzle_at_distance(&puzzle.fields, &puzzle.swaps, 2, 2, puzzle.distance);
+            let optimal = ida_star::find_swap_order(&puzzle.fields, 2, 2, 1_000).unwrap();
+            assert_eq!(optimal.len(), puzzle.distance);
+            num_at_distance[puzzle.distance - 5] += 1;
+        }
+        assert!(num_at_distance.iter().all(|&num| num > 0));
+    }
+
+    #[test]
+    fn distance_out_of_reach() {
+        let mut rng = StdRng::seed_from_u64(5);
+
+        // No 2x2 puzzle needs more than 6 swaps
+        assert!(matches!(
+            random_fields_at_distance(2, 2, 7..=10, 1_000, &mut rng),
+            Err(LibError::NoPuzzleAtDistance { min: 7, max: 10 })
+        ));
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = 5..=4;
+        assert!(matches!(
+            random_fields_at_distance(3, 3, empty, 1_000, &mut rng),
+            Err(LibError::NoPuzzleAtDistance { .. })
+        ));
+    }
+}
+
+#[cfg(test)]
+mod notation_tests {
+    use crate::{
+        board::get_empty_field_idx,
+        solver::{
+            divide_and_conquer::DacPuzzleSolver,
+            notation::{format_swaps, format_swaps_compact, parse_swaps},
+            verify::verify_solution,
+        },
+    };
+
+    #[test]
+    fn notation_roundtrips_dac_solutions() {
+        use super::examples::shuffled_fields;
+
+        for (width, height) in [(3, 3), (4, 4), (5, 3), (2, 6), (10, 10)] {
+            let fields = shuffled_fields(width, height, 1_000, 0);
+            let mut solver = DacPuzzleSolver::new(&fields, width as i32, height as i32).unwrap();
+            let swaps = solver.solve_puzzle().unwrap();
+            let empty_field_idx = get_empty_field_idx(&fields).unwrap();
+
+            let notation = format_swaps(&swaps, width, height).unwrap();
+            let compact = format_swaps_compact(&swaps, width, height).unwrap();
+            assert_eq!(notation.len(), swaps.len());
+            assert!(compact.len() <= notation.len());
+
+            for notation in [notation, compact] {
+                let parsed = parse_swaps(&notation, empty_field_idx, width, height).unwrap();
+                assert_eq!(parsed, swaps, "{width}x{height}");
+                verify_solution(&fields, width, height, &parsed).unwrap();
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Verification of solutions
+//!
+//! Solutions from the solvers, from tests or submitted by users are checked
+//! swap by swap. Every swap has to move the empty field to an orthogonal
+//! neighbour within the board, and the swaps have to end in the solved state.
+//! The first violation is reported with its step index and reason.
+//!
+
+use crate::{
+    board::{get_empty_field_idx, get_swappable_neighbours, TileId},
+    error::{InvalidSwapReason, LibError},
+};
+
+/// Check that the swaps solve the puzzle.
+pub fn verify_solution<T: TileId>(
+    fields: &[T],
+    width: usize,
+    height: usize,
+    swaps: &[(usize, usize)],
+) -> Result<(), LibError> {
+    if fields.len() != width * height {
+        return Err(LibError::FieldsBoardMismatch {
+            len: fields.len(),
+            expected: (width * height) as i32,
+        });
+    }
+
+    let mut state = fields.to_owned();
+    let mut empty_field_idx = get_empty_field_idx(fields)?;
+    for (step, &swap) in swaps.iter().enumerate() {
+        check_swap(empty_field_idx, swap, width, height)
+            .map_err(|reason| LibError::InvalidSwap { step, swap, reason })?;
+
+        state.swap(swap.0, swap.1);
+        empty_field_idx = swap.1;
+    }
+
+    let num_misplaced = state
+        .iter()
+        .enumerate()
+        .filter(|&(idx, &value)| value.to_usize() != idx)
+        .count();
+    if num_misplaced > 0 {
+        return Err(LibError::NotSolved {
+            num_swaps: swaps.len(),
+            num_misplaced,
+        });
+    }
+
+    Ok(())
+}
+
+/// Check that a swap moves the empty field to an orthogonal neighbour.
+pub(crate) fn check_swap(
+    empty_field_idx: usize,
+    (empty_idx_before, empty_idx_after): (usize, usize),
+    width: usize,
+    height: usize,
+) -> Result<(), InvalidSwapReason> {
+    for idx in [empty_idx_before, empty_idx_after] {
+        if idx >= width * height {
+            return Err(InvalidSwapReason::OutOfBounds(idx));
+        }
+    }
+
+    if empty_idx_before != empty_field_idx {
+        return Err(InvalidSwapReason::NotEmptyField { empty_field_idx });
+    }
+
+    if !get_swappable_neighbours(width, height, empty_field_idx)
+        .any(|neighbour_idx| neighbour_idx == empty_idx_after)
+    {
+        return Err(InvalidSwapReason::NotNeighbour);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_valid() -> Result<(), LibError> {
+        verify_solution(&[0u8, 1, 2, 3], 2, 2, &[])?;
+        verify_solution(&[0u8, 1, 3, 2], 2, 2, &[(2, 3)])?;
+        verify_solution(
+            &[8u8, 1, 2, 0, 3, 5, 6, 4, 7],
+            3,
+            3,
+            &[(0, 3), (3, 4), (4, 7), (7, 8)],
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_swaps() {
+        let fields: [u8; 9] = [0, 1, 2, 3, 4, 5, 6, 8, 7];
+        for (swaps, expected_step, expected_reason) in [
+            (vec![(7, 9)], 0, InvalidSwapReason::OutOfBounds(9)),
+            (
+                vec![(8, 7)],
+                0,
+                InvalidSwapReason::NotEmptyField { empty_field_idx: 7 },
+            ),
+            (vec![(7, 6), (6, 4)], 1, InvalidSwapReason::NotNeighbour),
+            (vec![(7, 7)], 0, InvalidSwapReason::NotNeighbour),
+            // Index 3 is next to index 2 in memory but in the row below
+            (
+                vec![(7, 4), (4, 1), (1, 2), (2, 3)],
+                3,
+                InvalidSwapReason::NotNeighbour,
+            ),
+        ] {
+            match verify_solution(&fields, 3, 3, &swaps) {
+                Err(LibError::InvalidSwap { step, swap, reason }) => {
+                    assert_eq!(step, expected_step);
+                    assert_eq!(swap, swaps[step]);
+                    assert_eq!(reason, expected_reason);
+                }
+                result => panic!("unexpected result {result:?} for {swaps:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_not_solved() {
+        let fields: [u8; 9] = [0, 1, 2, 3, 4, 5, 6, 8, 7];
+        assert!(matches!(
+            verify_solution(&fields, 3, 3, &[]),
+            Err(LibError::NotSolved {
+                num_swaps: 0,
+                num_misplaced: 2
+            })
+        ));
+        assert!(matches!(
+            verify_solution(&fields, 3, 3, &[(7, 8), (8, 7)]),
+            Err(LibError::NotSolved { num_swaps: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_board_mismatch() {
+        assert!(matches!(
+            verify_solution(&[0u8, 1, 2], 2, 2, &[]),
+            Err(LibError::FieldsBoardMismatch { len: 3, .. })
+        ));
+    }
+}
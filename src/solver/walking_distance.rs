@@ -0,0 +1,425 @@
+//! Walking distance heuristic
+//!
+//! The walking distance looks at rows and columns separately. For the rows, we
+//! only keep track of how many fields of each goal row are currently in each
+//! row and in which row the empty field is. Every vertical swap moves a single
+//! field to a neighbouring row, so the shortest number of such moves between a
+//! row distribution and the goal distribution is a lower bound for the number
+//! of vertical swaps. The same holds for columns and horizontal swaps. Unlike
+//! the Manhattan distance, this accounts for fields of the same row blocking
+//! each other.
+//!
+//! The distances of all row/column distributions are precomputed by a
+//! breadth-first-search from the goal distribution.
+//!
+
+use std::{collections::VecDeque, sync::Arc};
+
+use crate::{board::get_row_col_from_idx, error::LibError, solver::heuristic::Heuristic};
+
+/// Maximum number of lines (rows or columns) supported by the packed keys.
+const MAX_NUM_LINES: usize = 5;
+const UNSEEN: u8 = u8::MAX;
+
+/// Walking distance heuristic for boards from 2x2 up to 5x5.
+#[derive(Clone, Debug)]
+pub struct WalkingDistance {
+    width: usize,
+    rows: Arc<Table>,
+    columns: Arc<Table>,
+}
+
+impl WalkingDistance {
+    /// Precompute the walking distance tables for a board.
+    ///
+    /// The tables of boards up to 4x4 are built instantly. A 5x5 board has
+    /// about 66 million row distributions, its table takes around 66 MB of
+    /// memory and tens of seconds to build in a release build.
+    pub fn new(width: usize, height: usize) -> Result<Self, LibError> {
+        if !(2..=MAX_NUM_LINES).contains(&width) || !(2..=MAX_NUM_LINES).contains(&height) {
+            return Err(LibError::UnsupportedSize { width, height });
+        }
+
+        let rows = Arc::new(Table::build(height, width));
+        let columns = match width == height {
+            true => rows.clone(),
+            false => Arc::new(Table::build(width, height)),
+        };
+        log::debug!(
+            "Walking distance tables with {} row and {} column distributions",
+            rows.distances.len(),
+            columns.distances.len()
+        );
+
+        Ok(Self {
+            width,
+            rows,
+            columns,
+        })
+    }
+}
+
+impl Heuristic for WalkingDistance {
+    fn estimate(&self, fields: &[u8]) -> usize {
+        let empty_field_val = fields.len() - 1;
+        let mut row_counts = [[0; MAX_NUM_LINES]; MAX_NUM_LINES];
+        let mut column_counts = [[0; MAX_NUM_LINES]; MAX_NUM_LINES];
+        let mut empty_field_pos = (0, 0);
+
+        for (idx, &value) in fields.iter().enumerate() {
+            let (row, col): (usize, usize) = get_row_col_from_idx(idx, self.width);
+            if value as usize == empty_field_val {
+                empty_field_pos = (row, col);
+            } else {
+                let (goal_row, goal_col) = get_row_col_from_idx(value as usize, self.width);
+                row_counts[row][goal_row] += 1;
+                column_counts[col][goal_col] += 1;
+            }
+        }
+
+        self.rows.distance(&row_counts, empty_field_pos.0)
+            + self.columns.distance(&column_counts, empty_field_pos.1)
+    }
+}
+
+/// Number of fields per line and goal line.
+///
+/// e.g. counts[1][0] = 2 -> two fields which belong to line 0 are in line 1
+type Counts = [[u8; MAX_NUM_LINES]; MAX_NUM_LINES];
+
+/// Distances of all distributions of fields over lines (rows or columns).
+///
+/// A 5x5 board has about 66 million distributions, so we store the distances
+/// densely indexed by the rank of a distribution. The distribution is ranked
+/// line by line: every line is a composition of its number of fields over the
+/// goal lines and the rank counts the distributions with a smaller composition
+/// in an earlier line. The last line is fully determined by the others.
+#[derive(Debug)]
+struct Table {
+    num_lines: usize,
+    line_len: usize,
+    /// Number of distributions with the empty field in an earlier line.
+    empty_line_offsets: Vec<u32>,
+    /// Rank offsets of the compositions of a line, indexed by the empty line,
+    /// the line and the key of the remaining fields per goal line. The last
+    /// entry holds the number of all completions.
+    line_offsets: Vec<Vec<Option<Box<[u32]>>>>,
+    /// Index of a composition among all compositions with the same sum.
+    composition_idx: Vec<u16>,
+    /// Compositions with `line_len - 1` and `line_len` fields.
+    compositions: [Vec<[u8; MAX_NUM_LINES]>; 2],
+    distances: Vec<u8>,
+}
+
+impl Table {
+    /// Build the table by a breadth-first-search from the goal distribution.
+    fn build(num_lines: usize, line_len: usize) -> Self {
+        let mut table = Self::with_ranking(num_lines, line_len);
+
+        // In the goal distribution, every line holds its own fields except
+        // the last line which also holds the empty field.
+        let mut goal_counts = [[0; MAX_NUM_LINES]; MAX_NUM_LINES];
+        for (line, line_counts) in goal_counts.iter_mut().enumerate().take(num_lines) {
+            line_counts[line] = line_len as u8;
+        }
+        goal_counts[num_lines - 1][num_lines - 1] -= 1;
+
+        let goal_rank = table.rank(&goal_counts, num_lines - 1);
+        table.distances[goal_rank] = 0;
+        let mut to_explore = VecDeque::from([table.pack(&goal_counts, num_lines - 1)]);
+
+        while let Some(key) = to_explore.pop_front() {
+            let (mut counts, empty_line) = table.unpack(key);
+            let distance = table.distances[table.rank(&counts, empty_line)];
+
+            // Move a field from a neighbouring line into the line of the empty
+            // field, which moves the empty field to the neighbouring line.
+            let neighbour_lines = [empty_line.checked_sub(1), Some(empty_line + 1)];
+            for neighbour_line in neighbour_lines.into_iter().flatten() {
+                if neighbour_line >= num_lines {
+                    continue;
+                }
+
+                for goal_line in 0..num_lines {
+                    if counts[neighbour_line][goal_line] == 0 {
+                        continue;
+                    }
+
+                    counts[neighbour_line][goal_line] -= 1;
+                    counts[empty_line][goal_line] += 1;
+
+                    let next_rank = table.rank(&counts, neighbour_line);
+                    if table.distances[next_rank] == UNSEEN {
+                        table.distances[next_rank] = distance + 1;
+                        to_explore.push_back(table.pack(&counts, neighbour_line));
+                    }
+
+                    counts[neighbour_line][goal_line] += 1;
+                    counts[empty_line][goal_line] -= 1;
+                }
+            }
+        }
+
+        table
+    }
+
+    /// Set up the ranking of distributions with an unexplored distance each.
+    fn with_ranking(num_lines: usize, line_len: usize) -> Self {
+        let mut compositions = [Vec::new(), Vec::new()];
+        let mut composition_idx = vec![0; (line_len + 1).pow(num_lines as u32)];
+        for (sum_idx, sum) in [line_len - 1, line_len].into_iter().enumerate() {
+            compositions[sum_idx] = get_compositions(sum, num_lines);
+            for (idx, composition) in compositions[sum_idx].iter().enumerate() {
+                composition_idx[line_key(composition, num_lines, line_len)] = idx as u16;
+            }
+        }
+
+        let mut table = Self {
+            num_lines,
+            line_len,
+            empty_line_offsets: Vec::with_capacity(num_lines),
+            line_offsets: vec![vec![None; composition_idx.len()]; num_lines * (num_lines - 1)],
+            composition_idx,
+            compositions,
+            distances: Vec::new(),
+        };
+
+        let mut num_distributions = 0;
+        for empty_line in 0..num_lines {
+            table.empty_line_offsets.push(num_distributions);
+            let remaining = table.goal_line_sums();
+            num_distributions += table.build_line_offsets(empty_line, 0, remaining);
+        }
+        table.distances = vec![UNSEEN; num_distributions as usize];
+
+        table
+    }
+
+    /// Compute the rank offsets of a line given the remaining fields per goal
+    /// line and return the number of completions.
+    fn build_line_offsets(
+        &mut self,
+        empty_line: usize,
+        line: usize,
+        remaining: [u8; MAX_NUM_LINES],
+    ) -> u32 {
+        // The last line takes all remaining fields.
+        if line == self.num_lines - 1 {
+            return 1;
+        }
+
+        let slot = empty_line * (self.num_lines - 1) + line;
+        let key = line_key(&remaining, self.num_lines, self.line_len);
+        if let Some(offsets) = &self.line_offsets[slot][key] {
+            return offsets[offsets.len() - 1];
+        }
+
+        let sum_idx = (line != empty_line) as usize;
+        let compositions = self.compositions[sum_idx].clone();
+        let mut offsets = vec![u32::MAX; compositions.len() + 1];
+        let mut num_completions = 0;
+        for (idx, composition) in compositions.iter().enumerate() {
+            if (0..self.num_lines).all(|goal_line| composition[goal_line] <= remaining[goal_line]) {
+                let mut next_remaining = remaining;
+                for goal_line in 0..self.num_lines {
+                    next_remaining[goal_line] -= composition[goal_line];
+                }
+                offsets[idx] = num_completions;
+                num_completions += self.build_line_offsets(empty_line, line + 1, next_remaining);
+            }
+        }
+        offsets[compositions.len()] = num_completions;
+
+        self.line_offsets[slot][key] = Some(offsets.into_boxed_slice());
+        num_completions
+    }
+
+    /// Number of fields belonging to each goal line.
+    fn goal_line_sums(&self) -> [u8; MAX_NUM_LINES] {
+        let mut sums = [0; MAX_NUM_LINES];
+        sums[..self.num_lines].fill(self.line_len as u8);
+        sums[self.num_lines - 1] -= 1;
+        sums
+    }
+
+    fn rank(&self, counts: &Counts, empty_line: usize) -> usize {
+        let mut rank = self.empty_line_offsets[empty_line];
+        let mut remaining = self.goal_line_sums();
+
+        for (line, line_counts) in counts.iter().enumerate().take(self.num_lines - 1) {
+            let slot = empty_line * (self.num_lines - 1) + line;
+            let offsets = self.line_offsets[slot]
+                [line_key(&remaining, self.num_lines, self.line_len)]
+            .as_ref()
+            .expect("remaining fields of a valid distribution are ranked");
+            rank += offsets[self.composition_idx
+                [line_key(line_counts, self.num_lines, self.line_len)]
+                as usize];
+
+            for goal_line in 0..self.num_lines {
+                remaining[goal_line] -= line_counts[goal_line];
+            }
+        }
+
+        rank as usize
+    }
+
+    fn distance(&self, counts: &Counts, empty_line: usize) -> usize {
+        self.distances[self.rank(counts, empty_line)] as usize
+    }
+
+    /// Pack all but the last line with three bits per count together with the
+    /// line of the empty field.
+    fn pack(&self, counts: &Counts, empty_line: usize) -> u64 {
+        let mut key = empty_line as u64;
+        for line_counts in counts.iter().take(self.num_lines - 1) {
+            for &count in line_counts.iter().take(self.num_lines) {
+                key = (key << 3) | count as u64;
+            }
+        }
+        key
+    }
+
+    /// Inverse of [`Table::pack`].
+    fn unpack(&self, mut key: u64) -> (Counts, usize) {
+        let mut counts = [[0; MAX_NUM_LINES]; MAX_NUM_LINES];
+        for line_counts in counts.iter_mut().take(self.num_lines - 1).rev() {
+            for count in line_counts.iter_mut().take(self.num_lines).rev() {
+                *count = (key & 0b111) as u8;
+                key >>= 3;
+            }
+        }
+
+        // The last line holds all remaining fields
+        let mut last_line = self.goal_line_sums();
+        for line_counts in counts.iter().take(self.num_lines - 1) {
+            for goal_line in 0..self.num_lines {
+                last_line[goal_line] -= line_counts[goal_line];
+            }
+        }
+        counts[self.num_lines - 1] = last_line;
+
+        (counts, key as usize)
+    }
+}
+
+/// Get all ways to distribute `sum` fields over `num_lines` goal lines.
+fn get_compositions(sum: usize, num_lines: usize) -> Vec<[u8; MAX_NUM_LINES]> {
+    let mut compositions = Vec::new();
+    let mut composition = [0; MAX_NUM_LINES];
+
+    fn fill(
+        goal_line: usize,
+        remaining: u8,
+        num_lines: usize,
+        composition: &mut [u8; MAX_NUM_LINES],
+        compositions: &mut Vec<[u8; MAX_NUM_LINES]>,
+    ) {
+        if goal_line == num_lines - 1 {
+            composition[goal_line] = remaining;
+            compositions.push(*composition);
+            return;
+        }
+        for count in 0..=remaining {
+            composition[goal_line] = count;
+            fill(
+                goal_line + 1,
+                remaining - count,
+                num_lines,
+                composition,
+                compositions,
+            );
+        }
+    }
+
+    fill(0, sum as u8, num_lines, &mut composition, &mut compositions);
+    compositions
+}
+
+/// Key of the counts of a line with base `line_len + 1`.
+fn line_key(line_counts: &[u8; MAX_NUM_LINES], num_lines: usize, line_len: usize) -> usize {
+    line_counts
+        .iter()
+        .take(num_lines)
+        .fold(0, |key, &count| key * (line_len + 1) + count as usize)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::{
+        board::initialize_fields,
+        solver::{heuristic::ManhattanDistance, test::state_space::all_distances},
+    };
+
+    #[test]
+    fn test_zero_on_goal() -> Result<(), LibError> {
+        for size in 2..=4 {
+            let heuristic = WalkingDistance::new(size, size)?;
+            assert_eq!(
+                heuristic.estimate(&initialize_fields(size * size).unwrap()),
+                0
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_all_distributions_reached() {
+        for (num_lines, line_len) in [(2, 5), (3, 3), (4, 4), (5, 2)] {
+            let table = Table::build(num_lines, line_len);
+            assert!(table.distances.iter().all(|&distance| distance != UNSEEN));
+        }
+    }
+
+    #[test]
+    fn test_number_of_states_4x4() {
+        // Known number of row distributions of the 15-puzzle
+        assert_eq!(Table::build(4, 4).distances.len(), 24964);
+    }
+
+    #[test]
+    fn test_admissible_3x3() -> Result<(), LibError> {
+        let heuristic = WalkingDistance::new(3, 3)?;
+        let manhattan = ManhattanDistance::new(3, 3);
+
+        for (fields, distance) in all_distances(3, 3) {
+            let estimate = heuristic.estimate(&fields);
+            assert!(estimate <= distance, "{fields:?}: {estimate} > {distance}");
+            assert!(estimate >= manhattan.estimate(&fields));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_admissible_rectangular() -> Result<(), LibError> {
+        for (width, height) in [(3, 2), (2, 4)] {
+            let heuristic = WalkingDistance::new(width, height)?;
+            for (fields, distance) in all_distances(width, height) {
+                assert!(heuristic.estimate(&fields) <= distance);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_blocking_fields_in_row() -> Result<(), LibError> {
+        // 2 1 0 in the first row have to pass each other
+        let fields = [2, 1, 0, 3, 4, 5, 6, 7, 8];
+        assert_eq!(ManhattanDistance::new(3, 3).estimate(&fields), 4);
+        assert!(WalkingDistance::new(3, 3)?.estimate(&fields) > 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsupported_size() {
+        assert!(matches!(
+            WalkingDistance::new(6, 6),
+            Err(LibError::UnsupportedSize {
+                width: 6,
+                height: 6
+            })
+        ));
+    }
+}
@@ -0,0 +1,429 @@
+//! Bounded-suboptimal puzzle solver based on weighted A*
+//!
+//! A* expands states in the order of their estimated total path length
+//! `g + h`, where `g` is the number of swaps so far and `h` is an estimate of
+//! the remaining swaps by a [`Heuristic`]. Weighted A* inflates the estimate to
+//! `g + (1 + epsilon) * h`, which leads the search much faster towards the goal
+//! at the cost of optimality. Since the heuristics never overestimate, the
+//! solution is at most `1 + epsilon` times as long as a shortest one.
+//!
+//! States are reopened whenever a shorter path to them is found. This keeps a
+//! state of a shortest path with its exact `g` in the open set at any time, so
+//! the smallest `g + h` of the open set is a lower bound of the optimal solution
+//! length. Besides the swaps, we report this bound, which is often much closer
+//! to the solution length than the weight suggests.
+//!
+
+use std::{cmp::Ordering, collections::BinaryHeap, sync::Arc};
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    board::{ensure_solvable, get_empty_field_idx, get_swappable_neighbours, initialize_fields},
+    error::LibError,
+    solver::{
+        heuristic::{Heuristic, LinearConflict},
+        move_metric::MoveMetric,
+        pattern_database::{select_heuristic, PatternDatabase},
+        progress::{NoProgress, ProgressObserver, ProgressReporter, REPORT_INTERVAL},
+        registry::{Puzzle, Solution, Solver, SolverInfo},
+        state_key::StateKey,
+    },
+};
+
+/// Suboptimality which usually solves 4x4 and 5x5 puzzles within seconds.
+pub const DEFAULT_EPSILON: f64 = 1.;
+
+/// The weight is applied in fixed point arithmetic with this scale.
+const WEIGHT_SCALE: usize = 1000;
+
+/// Find a swap order which is at most `1 + epsilon` times as long as a
+/// shortest one.
+///
+/// The search is guided by the Manhattan distance plus linear conflicts.
+/// `max_num_steps` limits the number of expanded states.
+pub fn find_swap_order(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    epsilon: f64,
+    max_num_steps: usize,
+) -> Result<BoundedOutcome, LibError> {
+    find_swap_order_with_progress(
+        fields,
+        width,
+        height,
+        epsilon,
+        max_num_steps,
+        &LinearConflict::new(width, height),
+        &mut NoProgress,
+    )
+}
+
+/// Find a bounded-suboptimal swap order guided by a given heuristic and report
+/// the progress.
+///
+/// The observer is called every few thousand expanded states with the number
+/// of swaps to the expanded state and may cancel the search.
+pub fn find_swap_order_with_progress<H: Heuristic>(
+    fields: &[u8],
+    width: usize,
+    height: usize,
+    epsilon: f64,
+    max_num_steps: usize,
+    heuristic: &H,
+    observer: &mut dyn ProgressObserver,
+) -> Result<BoundedOutcome, LibError> {
+    if !(epsilon >= 0. && epsilon.is_finite()) {
+        return Err(LibError::InvalidEpsilon(epsilon));
+    }
+    ensure_solvable(fields, width, height)?;
+
+    // Round the weight down, which can only tighten the guarantee
+    let weight = ((1. + epsilon) * WEIGHT_SCALE as f64).floor() as usize;
+    let search = Search {
+        width,
+        height,
+        weight,
+        max_num_steps,
+        heuristic,
+        reporter: ProgressReporter::new(observer, REPORT_INTERVAL),
+    };
+
+    if u64::fits(fields.len()) {
+        search.run::<u64>(fields)
+    } else if u128::fits(fields.len()) {
+        search.run::<u128>(fields)
+    } else {
+        search.run::<Box<[u8]>>(fields)
+    }
+}
+
+/// Solution of a bounded-suboptimal search together with its quality
+/// guarantee.
+#[derive(Clone, Debug)]
+pub struct BoundedOutcome {
+    /// Swaps solving the puzzle.
+    pub swaps: Vec<(usize, usize)>,
+    /// Proven lower bound of the length of a shortest solution.
+    pub lower_bound: usize,
+    /// Number of expanded states.
+    pub num_expanded: usize,
+}
+
+impl BoundedOutcome {
+    /// Proven upper bound of the ratio between the length of the solution and
+    /// the length of a shortest solution.
+    pub fn suboptimality(&self) -> f64 {
+        match self.lower_bound {
+            0 => 1.,
+            lower_bound => self.swaps.len() as f64 / lower_bound as f64,
+        }
+    }
+}
+
+/// Weighted A* solver, see [`find_swap_order_with_progress`].
+///
+/// The search is guided by the pattern database, see [`select_heuristic`], or
+/// by the Manhattan distance plus linear conflicts.
+#[derive(Clone, Debug)]
+pub struct WeightedAStar {
+    pub epsilon: f64,
+    pub max_num_steps: usize,
+    pub pattern_database: Option<Arc<PatternDatabase>>,
+}
+
+impl Solver for WeightedAStar {
+    fn info(&self) -> SolverInfo {
+        SolverInfo {
+            name: "weighted-a-star",
+            optimal: false,
+            metric: MoveMetric::SingleTile,
+            square_only: false,
+            min_size: 2,
+            max_num_fields: 25,
+        }
+    }
+
+    fn solve_with_progress(
+        &self,
+        puzzle: &Puzzle,
+        observer: &mut dyn ProgressObserver,
+    ) -> Result<Solution, LibError> {
+        let Puzzle {
+            fields,
+            width,
+            height,
+        } = puzzle;
+
+        let heuristic = select_heuristic(
+            self.pattern_database.as_deref(),
+            *width,
+            *height,
+            LinearConflict::new(*width, *height),
+        );
+        let outcome = find_swap_order_with_progress(
+            fields,
+            *width,
+            *height,
+            self.epsilon,
+            self.max_num_steps,
+            &heuristic,
+            observer,
+        )?;
+
+        log::info!(
+            "Weighted A* found {} swaps, a shortest solution has at least {} swaps",
+            outcome.swaps.len(),
+            outcome.lower_bound
+        );
+
+        Ok(Solution {
+            swaps: outcome.swaps,
+        })
+    }
+}
+
+struct Search<'a, H> {
+    width: usize,
+    height: usize,
+    /// Weight of the heuristic, scaled by `WEIGHT_SCALE`.
+    weight: usize,
+    max_num_steps: usize,
+    heuristic: &'a H,
+    reporter: ProgressReporter<'a>,
+}
+
+impl<H: Heuristic> Search<'_, H> {
+    fn run<K: StateKey>(mut self, fields: &[u8]) -> Result<BoundedOutcome, LibError> {
+        let initial_key = K::pack(fields);
+        let target_key = K::pack(&initialize_fields(fields.len())?);
+        let empty_field_idx = get_empty_field_idx(fields)?;
+
+        // Map from a state key to the shortest known number of swaps to it,
+        // its parent key and the swap from the parent to this state.
+        let mut visited: FxHashMap<K, Visit<K>> = FxHashMap::default();
+        visited.insert(
+            initial_key.clone(),
+            // For the first state, the parent key and swap are never used.
+            Visit {
+                depth: 0,
+                parent_key: initial_key.clone(),
+                swap: (empty_field_idx, empty_field_idx),
+            },
+        );
+
+        let mut open = BinaryHeap::from([self.open_entry(
+            initial_key,
+            empty_field_idx,
+            0,
+            self.heuristic.estimate(fields),
+        )]);
+
+        // Buffer for the fields of the current state
+        let mut state = fields.to_owned();
+        let mut num_expanded = 0;
+
+        while let Some(entry) = open.pop() {
+            // Skip entries of states which were reached on a shorter path
+            // after the entry was added
+            if entry.depth > visited[&entry.state_key].depth {
+                continue;
+            }
+
+            if entry.state_key == target_key {
+                let swaps = trace_back(&visited, entry.state_key);
+                let lower_bound = self.lower_bound(&open, swaps.len());
+
+                log::debug!("Number of expanded states in solver: {}", num_expanded);
+                log::debug!(
+                    "Number of swaps to solve: {}, lower bound: {}",
+                    swaps.len(),
+                    lower_bound
+                );
+
+                return Ok(BoundedOutcome {
+                    swaps,
+                    lower_bound,
+                    num_expanded,
+                });
+            }
+
+            num_expanded += 1;
+            if num_expanded > self.max_num_steps {
+                return Err(LibError::MaxNumStepsReached(self.max_num_steps));
+            }
+            self.reporter.update(num_expanded, entry.depth)?;
+
+            entry.state_key.unpack_into(&mut state);
+            let next_depth = entry.depth + 1;
+
+            for neighbour_idx in get_swappable_neighbours(self.width, self.height, entry.empty_idx)
+            {
+                state.swap(entry.empty_idx, neighbour_idx);
+                let next_key = K::pack(&state);
+                let estimate = self.heuristic.estimate(&state);
+                state.swap(entry.empty_idx, neighbour_idx);
+
+                if visited
+                    .get(&next_key)
+                    .is_some_and(|visit| visit.depth <= next_depth)
+                {
+                    continue;
+                }
+
+                // New states and states with a shorter path are (re)opened
+                visited.insert(
+                    next_key.clone(),
+                    Visit {
+                        depth: next_depth,
+                        parent_key: entry.state_key.clone(),
+                        swap: (entry.empty_idx, neighbour_idx),
+                    },
+                );
+                open.push(self.open_entry(next_key, neighbour_idx, next_depth, estimate));
+            }
+        }
+
+        Err(LibError::TerminatedWithoutSolution)
+    }
+
+    fn open_entry<K>(
+        &self,
+        state_key: K,
+        empty_idx: usize,
+        depth: usize,
+        estimate: usize,
+    ) -> OpenEntry<K> {
+        OpenEntry {
+            priority: (depth * WEIGHT_SCALE).saturating_add(estimate.saturating_mul(self.weight)),
+            depth,
+            estimate,
+            state_key,
+            empty_idx,
+        }
+    }
+
+    /// Compute a lower bound of the optimal solution length once a solution
+    /// with `num_swaps` swaps is found.
+    ///
+    /// Both the smallest unweighted estimate of the open set and the solution
+    /// length divided by the weight are lower bounds, so we take the larger
+    /// one.
+    fn lower_bound<K>(&self, open: &BinaryHeap<OpenEntry<K>>, num_swaps: usize) -> usize {
+        let open_bound = open
+            .iter()
+            .map(|entry| entry.depth + entry.estimate)
+            .min()
+            .map_or(num_swaps, |open_bound| open_bound.min(num_swaps));
+        let weight_bound = (num_swaps * WEIGHT_SCALE).div_ceil(self.weight);
+
+        usize::max(open_bound, weight_bound)
+    }
+}
+
+/// Collect the swaps from the initial state to a state.
+fn trace_back<K: StateKey>(visited: &FxHashMap<K, Visit<K>>, mut key: K) -> Vec<(usize, usize)> {
+    let mut swaps = Vec::new();
+    while let Some(visit) = visited.get(&key) {
+        if visit.depth == 0 {
+            break;
+        }
+        swaps.push(visit.swap);
+        key = visit.parent_key.clone();
+    }
+    swaps.reverse();
+    swaps
+}
+
+struct Visit<K> {
+    depth: usize,
+    parent_key: K,
+    /// Empty field index before and after the swap.
+    swap: (usize, usize),
+}
+
+/// State in the open set, ordered such that the max-heap pops the smallest
+/// weighted estimate first and prefers deeper states on ties.
+struct OpenEntry<K> {
+    priority: usize,
+    depth: usize,
+    estimate: usize,
+    state_key: K,
+    empty_idx: usize,
+}
+
+impl<K> PartialEq for OpenEntry<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K> Eq for OpenEntry<K> {}
+
+impl<K> PartialOrd for OpenEntry<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K> Ord for OpenEntry<K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .cmp(&self.priority)
+            .then(self.depth.cmp(&other.depth))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_swap_order_zero_moves() -> Result<(), LibError> {
+        let fields = vec![0, 1, 2, 3];
+        let outcome = find_swap_order(&fields, 2, 2, DEFAULT_EPSILON, 10)?;
+        assert_eq!(outcome.swaps, Vec::with_capacity(0));
+        assert_eq!(outcome.lower_bound, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_swap_order_four_swaps() -> Result<(), LibError> {
+        let fields = vec![8, 1, 2, 0, 3, 5, 6, 4, 7];
+        let outcome = find_swap_order(&fields, 3, 3, 0., 256)?;
+        assert_eq!(outcome.swaps, vec![(0, 3), (3, 4), (4, 7), (7, 8)]);
+        assert_eq!(outcome.lower_bound, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_epsilon() {
+        let fields = vec![0, 1, 3, 2];
+        for epsilon in [-0.1, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                find_swap_order(&fields, 2, 2, epsilon, 10),
+                Err(LibError::InvalidEpsilon(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_max_num_steps_reached() {
+        let fields = vec![8, 1, 2, 0, 3, 5, 6, 4, 7];
+        assert!(matches!(
+            find_swap_order(&fields, 3, 3, DEFAULT_EPSILON, 2),
+            Err(LibError::MaxNumStepsReached(2))
+        ));
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        let fields = vec![1, 0, 2, 3];
+        assert!(matches!(
+            find_swap_order(&fields, 2, 2, DEFAULT_EPSILON, 1000),
+            Err(LibError::Unsolvable)
+        ));
+    }
+}
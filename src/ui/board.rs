@@ -3,6 +3,7 @@ use web_sys::{window, CssStyleDeclaration, Document, MouseEvent, Node};
 
 use crate::{
     board::{get_row_col_from_idx, Board},
+    error::LibError,
     ui::{search_params::Parameters, ui_locked},
     BOARD,
 };
@@ -22,9 +23,10 @@ impl UiBoard {
         &self.inner
     }
 
-    pub(crate) fn init(&mut self, params: Parameters) {
-        self.inner.init(params.size);
+    pub(crate) fn init(&mut self, params: Parameters) -> Result<(), LibError> {
+        self.inner.init(params.size)?;
         self.init_board_ui(params);
+        Ok(())
     }
 
     fn swap_ui_fields(&mut self, id_a: u8, id_b: u8) {
@@ -1,14 +1,27 @@
+use std::ops::ControlFlow;
+
 use wasm_bindgen::prelude::*;
 use web_sys::{window, CssStyleSheet, HtmlElement, MouseEvent};
 
 use crate::{
-    board::{get_empty_field_idx, get_shuffle_sequence},
-    solver::{divide_and_conquer::DacPuzzleSolver, optimal::find_swap_order},
+    board::{get_empty_field_idx, get_shuffle_sequence, initialize_fields},
+    error::LibError,
+    solver::{
+        generator::{
+            get_swaps_between, random_fields_at_distance, random_solvable_fields, Difficulty,
+        },
+        hint::{best_next_move, Hint},
+        move_metric::{MoveMetric, Slide},
+        notation::format_swaps_compact,
+        peephole,
+        progress::Progress,
+        registry::{Puzzle, Solution, SolverConfig, SolverRegistry},
+    },
     ui::{
         lock_ui,
         touch::{get_touch_end_callback, get_touch_move_callback, get_touch_start_callback},
     },
-    unlock_ui, BOARD,
+    unlock_ui, BOARD, PATTERN_DATABASE,
 };
 
 const NUM_SHUFFLES: usize = 10;
@@ -16,15 +29,37 @@ const SWAP_TIMEOUT_FAST: i32 = 250;
 const SWAP_TIMEOUT_SLOW: i32 = 500;
 const MAX_NUM_STEPS: usize = 10_000_000;
 
-pub(crate) fn setup_button_callbacks(size: usize) {
+pub(crate) fn setup_button_callbacks(
+    size: usize,
+    metric: MoveMetric,
+    difficulty: Option<Difficulty>,
+) {
     let document = window().unwrap().document().unwrap();
 
     #[allow(clippy::type_complexity)]
-    let ids_get_callbacks: [(_, &dyn Fn(usize) -> Closure<dyn FnMut(MouseEvent)>); 4] = [
-        ("quick_swap", &get_quick_swap_callback),
-        ("granular_swap", &get_granular_swap_callback),
-        ("optimal_solve", &get_optimal_solve_callback),
-        ("d_and_c_solve", &get_dac_solve_callback),
+    let ids_get_callbacks: [(_, &dyn Fn(usize) -> Closure<dyn FnMut(MouseEvent)>); 6] = [
+        ("quick_swap", &|size| {
+            get_quick_swap_callback(size, difficulty)
+        }),
+        ("granular_swap", &|size| {
+            get_granular_swap_callback(size, difficulty)
+        }),
+        ("hint", &get_hint_callback),
+        ("optimal_solve", &|size| {
+            // The 3x3 board has a table of all solutions in the single-tile
+            // metric, larger ones and the multi-tile metric need a search
+            let solver_name = match (size, metric) {
+                (3, MoveMetric::SingleTile) => "lookup-table",
+                _ => "ida-star",
+            };
+            get_solve_callback(size, solver_name, metric)
+        }),
+        ("near_optimal_solve", &|size| {
+            get_solve_callback(size, "weighted-a-star", metric)
+        }),
+        ("d_and_c_solve", &|size| {
+            get_solve_callback(size, "divide-and-conquer", metric)
+        }),
     ];
 
     for (id, get_callback) in ids_get_callbacks {
@@ -55,39 +90,83 @@ pub(crate) fn setup_button_callbacks(size: usize) {
     touch_end_callback.forget();
 }
 
-fn get_quick_swap_callback(size: usize) -> Closure<dyn FnMut(MouseEvent)> {
+/// Get swaps from the current state to a random puzzle of the difficulty, or to
+/// a uniformly random state without one.
+fn get_random_shuffle(
+    ids: &[u8],
+    size: usize,
+    difficulty: Option<Difficulty>,
+) -> Result<Vec<(usize, usize)>, LibError> {
+    let mut rng = rand::thread_rng();
+    let Some(difficulty) = difficulty else {
+        let target = random_solvable_fields(size, size, &mut rng)?;
+        return get_swaps_between(ids, &target, size, size);
+    };
+
+    let puzzle =
+        random_fields_at_distance(size, size, difficulty.distances(), MAX_NUM_STEPS, &mut rng)?;
+    log::info!("Shuffled to a puzzle at distance {}", puzzle.distance);
+
+    // The swaps of the puzzle start at the solved state
+    match ids == initialize_fields(ids.len())? {
+        true => Ok(puzzle.swaps),
+        false => get_swaps_between(ids, &puzzle.fields, size, size),
+    }
+}
+
+fn get_quick_swap_callback(
+    size: usize,
+    difficulty: Option<Difficulty>,
+) -> Closure<dyn FnMut(MouseEvent)> {
     Closure::wrap(Box::new(move |_| {
         if !lock_ui() {
             return;
         }
 
-        let empty_field_idx =
-            BOARD.with_borrow(|b| get_empty_field_idx(b.board().fields()).unwrap());
-
-        let shuffle_sequence = get_shuffle_sequence(size, empty_field_idx, 20);
-        log::info!("Shuffle sequence: {:?}", &shuffle_sequence);
-
-        BOARD.with_borrow_mut(|b| {
-            for swap in shuffle_sequence {
-                b.swap_indices(swap.0, swap.1);
+        // Jump to a random state instead of a short random walk
+        let ids = BOARD.with_borrow(|b| b.board().fields().clone());
+        let shuffle_sequence = get_random_shuffle(&ids, size, difficulty);
+
+        match shuffle_sequence {
+            Ok(shuffle_sequence) => {
+                log::info!("Shuffle sequence: {:?}", &shuffle_sequence);
+                BOARD.with_borrow_mut(|b| {
+                    for swap in shuffle_sequence {
+                        b.swap_indices(swap.0, swap.1);
+                    }
+                });
             }
-        });
+            Err(err) => log::error!("failed to shuffle puzzle: {err}"),
+        }
 
         unlock_ui();
     }))
 }
 
-fn get_granular_swap_callback(size: usize) -> Closure<dyn FnMut(MouseEvent)> {
+fn get_granular_swap_callback(
+    size: usize,
+    difficulty: Option<Difficulty>,
+) -> Closure<dyn FnMut(MouseEvent)> {
     Closure::wrap(Box::new(move |_| {
         if !lock_ui() {
             return;
         }
 
-        let num_shuffles = NUM_SHUFFLES;
-        let empty_field_idx =
-            BOARD.with_borrow(|b| get_empty_field_idx(b.board().fields()).unwrap());
-
-        let shuffle_sequence = get_shuffle_sequence(size, empty_field_idx, num_shuffles);
+        // Animate the way to a puzzle of the difficulty, or a short random
+        // walk without one
+        let ids = BOARD.with_borrow(|b| b.board().fields().clone());
+        let shuffle_sequence = match difficulty {
+            Some(_) => match get_random_shuffle(&ids, size, difficulty) {
+                Ok(shuffle_sequence) => shuffle_sequence,
+                Err(err) => {
+                    log::error!("failed to shuffle puzzle: {err}");
+                    unlock_ui();
+                    return;
+                }
+            },
+            None => get_shuffle_sequence(size, get_empty_field_idx(&ids).unwrap(), NUM_SHUFFLES),
+        };
+        let num_shuffles = shuffle_sequence.len();
         log::info!("Shuffle sequence: {:?}", &shuffle_sequence);
 
         let window = window().unwrap();
@@ -127,44 +206,117 @@ fn get_granular_swap_callback(size: usize) -> Closure<dyn FnMut(MouseEvent)> {
     }))
 }
 
-fn get_optimal_solve_callback(size: usize) -> Closure<dyn FnMut(MouseEvent)> {
+fn get_hint_callback(size: usize) -> Closure<dyn FnMut(MouseEvent)> {
     Closure::wrap(Box::new(move |_| {
         if !lock_ui() {
             return;
         }
 
         let ids = BOARD.with_borrow(|b| b.board().fields().clone());
-        match find_swap_order(&ids, size, size, MAX_NUM_STEPS) {
-            Ok(solve_sequence) => {
-                apply_solve_sequence(solve_sequence, SWAP_TIMEOUT_SLOW);
+        let hint = PATTERN_DATABASE
+            .with_borrow(|database| best_next_move(&ids, size, size, database.as_deref()));
+
+        match hint {
+            Ok(Hint {
+                swap: Some(swap),
+                distance,
+                exact,
+            }) => {
+                let qualifier = if exact { "optimally" } else { "at most" };
+                log::info!("Hint: {swap:?}, solvable in {qualifier} {distance} swaps");
+                let slide = Slide {
+                    from: swap.0,
+                    to: swap.1,
+                };
+                apply_solve_sequence(vec![slide], size, SWAP_TIMEOUT_SLOW);
+            }
+            Ok(Hint { swap: None, .. }) => {
+                log::info!("Hint: the puzzle is already solved");
+                unlock_ui();
             }
             Err(err) => {
-                log::error!("failed to find optimal solve sequence: {err}");
+                log::error!("failed to get a hint: {err}");
                 unlock_ui();
             }
         }
     }))
 }
 
-fn get_dac_solve_callback(size: usize) -> Closure<dyn FnMut(MouseEvent)> {
+fn get_solve_callback(
+    size: usize,
+    solver_name: &'static str,
+    metric: MoveMetric,
+) -> Closure<dyn FnMut(MouseEvent)> {
     Closure::wrap(Box::new(move |_| {
         if !lock_ui() {
             return;
         }
 
         let ids = BOARD.with_borrow(|b| b.board().fields().clone());
-        match DacPuzzleSolver::new(&ids, size as i32, size as i32) {
-            Ok(mut solver) => match solver.solve_puzzle() {
-                Ok(solve_sequence) => {
-                    apply_solve_sequence(solve_sequence, SWAP_TIMEOUT_SLOW);
-                }
-                Err(err) => {
-                    log::error!("failed to solve puzzle: {err}");
-                    unlock_ui();
+
+        // IDA* and weighted A* use a loaded pattern database if it matches
+        // the board
+        let registry = SolverRegistry::new(SolverConfig {
+            max_num_steps: MAX_NUM_STEPS,
+            pattern_database: PATTERN_DATABASE.with_borrow(|database| database.clone()),
+            move_metric: metric,
+            ..Default::default()
+        });
+        let solver = registry
+            .get(solver_name)
+            .expect("solver of button is registered");
+
+        let mut log_progress = |progress: &Progress| {
+            log::debug!(
+                "{solver_name}: {} expanded, depth {}, {:?} elapsed",
+                progress.num_expanded,
+                progress.depth,
+                progress.elapsed
+            );
+            ControlFlow::Continue(())
+        };
+
+        let result = Puzzle::new(ids, size, size).and_then(|puzzle| {
+            let solution = solver.solve_with_progress(&puzzle, &mut log_progress)?;
+            if solver.info().optimal {
+                return Ok(solution);
+            }
+
+            // Remove detours of non-optimal solvers before animating
+            let outcome = peephole::optimize(
+                &puzzle.fields,
+                size,
+                size,
+                &solution.swaps,
+                peephole::DEFAULT_WINDOW_SIZE,
+            )?;
+            log::info!("Peephole optimizer saved {} swaps", outcome.num_saved);
+            Ok(Solution {
+                swaps: outcome.swaps,
+            })
+        });
+
+        match result {
+            Ok(solution) => {
+                match format_swaps_compact(&solution.swaps, size, size) {
+                    Ok(notation) => log::info!("Solution: {notation}"),
+                    Err(err) => log::error!("failed to format solution: {err}"),
                 }
-            },
+
+                // Animate a slide of several fields as one move in the
+                // multi-tile metric
+                let moves = match metric {
+                    MoveMetric::SingleTile => solution
+                        .swaps
+                        .iter()
+                        .map(|&(from, to)| Slide { from, to })
+                        .collect(),
+                    MoveMetric::MultiTile => solution.slides(),
+                };
+                apply_solve_sequence(moves, size, SWAP_TIMEOUT_SLOW);
+            }
             Err(err) => {
-                log::error!("failed to create divide&conquer solver: {err}");
+                log::error!("failed to solve puzzle with {solver_name}: {err}");
                 unlock_ui();
             }
         }
@@ -177,16 +329,26 @@ fn get_swap_callback(swap: (usize, usize)) -> Closure<dyn FnMut()> {
     }))
 }
 
-fn apply_solve_sequence(solve_sequence: Vec<(usize, usize)>, interval: i32) {
+fn get_slide_callback(slide: Slide, size: usize) -> Closure<dyn FnMut()> {
+    Closure::wrap(Box::new(move || {
+        BOARD.with_borrow_mut(|b| {
+            for swap in slide.swaps(size) {
+                b.swap_indices(swap.0, swap.1);
+            }
+        });
+    }))
+}
+
+fn apply_solve_sequence(solve_sequence: Vec<Slide>, size: usize, interval: i32) {
     log::info!("Solve sequence: {:?}", &solve_sequence);
-    let num_swaps = solve_sequence.len();
+    let num_moves = solve_sequence.len();
 
     let window = window().unwrap();
-    let mut callbacks = Vec::with_capacity(num_swaps);
+    let mut callbacks = Vec::with_capacity(num_moves);
 
-    // Send every shuffle with a separate timeout.
-    for (i, swap) in solve_sequence.into_iter().enumerate() {
-        let callback = get_swap_callback(swap);
+    // Send every move with a separate timeout.
+    for (i, slide) in solve_sequence.into_iter().enumerate() {
+        let callback = get_slide_callback(slide, size);
         let millis = (i as i32 + 1) * interval;
 
         window
@@ -211,7 +373,7 @@ fn apply_solve_sequence(solve_sequence: Vec<(usize, usize)>, interval: i32) {
     window
         .set_timeout_with_callback_and_timeout_and_arguments_0(
             finish_callback.as_ref().unchecked_ref(),
-            (num_swaps as i32 + 1) * interval,
+            (num_moves as i32 + 1) * interval,
         )
         .unwrap();
     finish_callback.forget();
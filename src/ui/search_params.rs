@@ -2,6 +2,8 @@ use std::collections::BTreeMap;
 
 use web_sys::window;
 
+use crate::solver::{generator::Difficulty, move_metric::MoveMetric};
+
 const DEFAULT_SIZE: usize = 3;
 const DEFAULT_BACKGROUND: &str = "https://upload.wikimedia.org/wikipedia/commons/thumb/6/61/Blue_Marble_Western_Hemisphere.jpg/600px-Blue_Marble_Western_Hemisphere.jpg?20130305115950";
 
@@ -25,13 +27,35 @@ pub(crate) fn extract_parameters() -> Parameters {
         .map(ToOwned::to_owned)
         .unwrap_or_else(|| DEFAULT_BACKGROUND.to_owned());
 
-    Parameters { size, bg_url }
+    // Slides of several fields count as one move with `metric=multi`
+    let metric = match params.get("metric").map(String::as_str) {
+        Some("multi") => MoveMetric::MultiTile,
+        _ => MoveMetric::SingleTile,
+    };
+
+    // Shuffles jump to puzzles of an exact optimal distance with
+    // `difficulty=easy|medium|hard`
+    let difficulty = match params.get("difficulty").map(String::as_str) {
+        Some("easy") => Some(Difficulty::Easy),
+        Some("medium") => Some(Difficulty::Medium),
+        Some("hard") => Some(Difficulty::Hard),
+        _ => None,
+    };
+
+    Parameters {
+        size,
+        bg_url,
+        metric,
+        difficulty,
+    }
 }
 
 #[derive(Debug)]
 pub(crate) struct Parameters {
     pub(crate) size: usize,
     pub(crate) bg_url: String,
+    pub(crate) metric: MoveMetric,
+    pub(crate) difficulty: Option<Difficulty>,
 }
 
 pub(crate) fn search_params() -> Option<String> {